@@ -5,14 +5,19 @@ view = View
 ## Navbar
 home = Home
 notifications = Notifications
+follow-requests = Follow requests
+conversations = Conversations
+scheduled = Scheduled
 search = Search
 favorites = Favorites
 bookmarks = Bookmarks
 hashtags = Hashtags
 lists = Lists
+who-to-follow = Who to follow
 explore = Explore
 local = Local
 federated = Federated
+moderation = Moderation
 
 ## About
 repository = Repository
@@ -38,9 +43,173 @@ status = Status
 switch-instance = Switch instance
 logout-question = Are you sure you want to logout?
 logout-description = You will need to login again to access your account.
+edit-post = Edit post
+delete-question = Delete this post?
+delete-description = The post is removed from the server along with its boosts and favourites.
+redraft-description = The post is removed from the server and opened in the composer with its attachments and poll. Boosts, favourites and votes are lost.
 
 ## Actions
 reply = Reply
+new-post = New post
+post = Post
+schedule = Schedule
+reschedule = Reschedule
+delete = Delete
+delete-and-redraft = Delete & redraft
+edit = Edit
+save = Save
+pin = Pin on profile
+unpin = Unpin from profile
+mute-conversation = Mute conversation
+unmute-conversation = Unmute conversation
+open = Open
 cancel = Cancel
+accept = Accept
+reject = Reject
+approve = Approve
+follow = Follow
+dismiss = Dismiss
+resolve = Resolve
+mark-sensitive = Mark as sensitive
+silence = Limit
+suspend = Suspend
+mark-sensitive-question = Mark this account as sensitive?
+mark-sensitive-description = Media the account posts is hidden behind a warning, and the report is resolved.
+silence-question = Limit this account?
+silence-description = Posts of the account are only shown to its followers, and the report is resolved.
+suspend-question = Suspend this account?
+suspend-description = The account can no longer be used and its posts are removed from the server, and the report is resolved.
+open-moderation = Open moderation
 login = Login
 confirm = Confirm
+
+## Timelines
+refresh = Refresh
+loading = Loading…
+load-missing-posts = Load missing posts
+end-of-timeline = You have reached the end
+mark-all-read = Mark all as read
+mark-as-read = Mark as read
+last-read = Last read
+page-unread = { $page } ({ $count })
+no-follow-requests = No pending follow requests
+no-conversations = No direct messages yet
+scheduled-posts = Scheduled posts
+no-scheduled-posts = No scheduled posts
+scheduled-for = Posted at { $time }
+drafts = Drafts
+no-drafts = No drafts, posts closed with Escape are kept here
+draft-saved = Saved at { $time }
+schedule-for = Post at
+direct-reply = Only the people mentioned will see this reply.
+open-reports = Open reports
+pending-sign-ups = Pending sign-ups
+no-reports = No open reports
+no-sign-ups = No pending sign-ups
+reported-by = Reported by @{ $acct } for { $category }
+no-hashtag = Select a hashtag in a post to see its timeline
+empty-home = Your home timeline is empty. Follow some people to fill it.
+find-people = Find people to follow
+no-suggestions = No suggestions right now
+
+## Explore
+posts = Posts
+links = News
+people-talking = { $count ->
+    [one] 1 person
+   *[other] { $count } people
+} talking in the past { $days } days
+link-shares = Shared { $count ->
+    [one] once
+   *[other] { $count } times
+} in the past { $days } days
+
+## Settings
+settings = Settings
+appearance = Appearance
+play-animations = Play animations
+play-animations-description = Animate avatars and custom emoji. When off, their static versions are shown.
+desktop-notifications = Desktop notifications
+mentions = Mentions
+boosts = Boosts
+favourites = Favourites
+follows = Follows and follow requests
+polls = Ended polls
+statuses = Posts from accounts you get notified about
+edits = Edits of posts you boosted
+admin = Sign-ups and reports
+request-moderation = Request moderation access
+request-moderation-description = Ask for the admin scopes moderators need when logging in. Log in again for changes to apply.
+keyboard-shortcuts = Keyboard shortcuts
+shortcut-next = Next post
+shortcut-previous = Previous post
+shortcut-favourite = Favourite
+shortcut-boost = Boost
+shortcut-reply = Reply
+shortcut-compose = New post
+shortcut-open = Open thread
+shortcut-home = Go to home
+shortcut-notifications = Go to notifications
+
+## Notifications
+notification-mention = { $name } mentioned you
+notification-reblog = { $others ->
+    [0] { $name } boosted your post
+    [one] { $name } and 1 other boosted your post
+   *[other] { $name } and { $others } others boosted your post
+}
+notification-favourite = { $others ->
+    [0] { $name } favourited your post
+    [one] { $name } and 1 other favourited your post
+   *[other] { $name } and { $others } others favourited your post
+}
+notification-follow = { $name } followed you
+notification-follow-request = { $name } requested to follow you
+notification-poll = A poll has ended
+notification-status = { $name } posted
+notification-update = { $name } edited a post
+notification-sign-up = { $name } signed up
+notification-report = A new report has been filed
+all = All
+filter-mentions = Mentions
+filter-boosts = Boosts
+filter-favourites = Favourites
+filter-follows = Follows
+filter-polls = Polls
+filter-edits = Edits
+filter-admin = Admin
+
+## Media viewer
+media-viewer = Media viewer
+previous = Previous
+next = Next
+play = Play
+pause = Pause
+open-original = Open original
+open-externally = Open externally
+close = Close
+no-description = No description
+no-media = No media
+
+## Errors
+retry = Retry
+error-auth = Your session is no longer valid. Please log in again.
+error-rate-limited = The server is limiting requests. Try again in a few minutes.
+offline = Offline, { $queued ->
+    [one] 1 action queued
+   *[other] { $queued } actions queued
+}
+pending = Waiting to be sent
+error-rate-limited-until = The server is limiting requests until { $time }.
+rate-limited-until = Rate limited until { $time }
+slowing-down-until = Slowing down requests until { $time }
+error-offline = You're offline. Actions are sent once the connection returns.
+error-network = Couldn't reach the server. Check your connection.
+error-login = Couldn't log in. Check the server address, then try again.
+error-keyring = Couldn't access the keyring to store your login.
+error-unknown = Something went wrong: { $reason }
+error-images = Some images couldn't be loaded.
+error-streaming = Live updates are unavailable, timelines update when refreshed.
+error-not-moderator = Moderation needs a login with moderation access, which can be requested in the settings.
+error-schedule-time = Scheduled times are written like 2025-01-31 18:30.
+error-schedule-soon = Posts can only be scheduled at least 5 minutes ahead.
@@ -5,14 +5,19 @@ view = Visa
 ## Navigeringsfältet
 home = Home
 notifications = Aviseringar
+follow-requests = Följförfrågningar
+conversations = Konversationer
+scheduled = Schemalagt
 search = Sök
 favorites = Favoriter
 bookmarks = Bokmärken
 hashtags = Hashtaggar
 lists = Listor
+who-to-follow = Vem du kan följa
 explore = Utforska
 local = Lokal
 federated = Federerat
+moderation = Moderering
 
 ## Om
 repository = Förråd
@@ -36,4 +41,168 @@ status = Status
 
 ## Åtgärder
 reply = Svara
+new-post = Nytt inlägg
+post = Publicera
+schedule = Schemalägg
+reschedule = Schemalägg om
+delete = Ta bort
+delete-and-redraft = Ta bort och skriv om
+edit = Redigera
+save = Spara
+pin = Fäst på profilen
+unpin = Lossa från profilen
+mute-conversation = Tysta konversation
+unmute-conversation = Sluta tysta konversation
+edit-post = Redigera inlägg
+delete-question = Ta bort inlägget?
+delete-description = Inlägget tas bort från servern tillsammans med dess knuffar och favoriter.
+redraft-description = Inlägget tas bort från servern och öppnas i redigeraren med sina bilagor och sin omröstning. Knuffar, favoriter och röster går förlorade.
+open = Öppna
 cancel = Avbryt
+accept = Acceptera
+reject = Avvisa
+approve = Godkänn
+follow = Följ
+dismiss = Avfärda
+resolve = Lös
+mark-sensitive = Markera som känsligt
+silence = Begränsa
+suspend = Stäng av
+mark-sensitive-question = Markera kontot som känsligt?
+mark-sensitive-description = Media som kontot publicerar döljs bakom en varning, och anmälan löses.
+silence-question = Begränsa kontot?
+silence-description = Kontots inlägg visas bara för dess följare, och anmälan löses.
+suspend-question = Stäng av kontot?
+suspend-description = Kontot kan inte längre användas och dess inlägg tas bort från servern, och anmälan löses.
+open-moderation = Öppna moderering
+
+## Tidslinjer
+refresh = Uppdatera
+loading = Laddar…
+load-missing-posts = Ladda saknade inlägg
+end-of-timeline = Du har nått slutet
+mark-all-read = Markera alla som lästa
+mark-as-read = Markera som läst
+last-read = Senast läst
+page-unread = { $page } ({ $count })
+no-follow-requests = Inga väntande följförfrågningar
+no-conversations = Inga direktmeddelanden än
+scheduled-posts = Schemalagda inlägg
+no-scheduled-posts = Inga schemalagda inlägg
+scheduled-for = Publiceras { $time }
+drafts = Utkast
+no-drafts = Inga utkast, inlägg som stängs med Escape sparas här
+draft-saved = Sparat { $time }
+schedule-for = Publicera
+direct-reply = Endast de som nämns kommer att se det här svaret.
+open-reports = Öppna anmälningar
+pending-sign-ups = Väntande registreringar
+no-reports = Inga öppna anmälningar
+no-sign-ups = Inga väntande registreringar
+reported-by = Anmäld av @{ $acct } för { $category }
+no-hashtag = Välj en hashtagg i ett inlägg för att se dess tidslinje
+empty-home = Din hemtidslinje är tom. Följ några personer för att fylla den.
+find-people = Hitta personer att följa
+no-suggestions = Inga förslag just nu
+
+## Explore
+posts = Inlägg
+links = Nyheter
+people-talking = { $count ->
+    [one] 1 person
+   *[other] { $count } personer
+} har pratat de senaste { $days } dagarna
+link-shares = Delad { $count ->
+    [one] en gång
+   *[other] { $count } gånger
+} de senaste { $days } dagarna
+
+## Inställningar
+settings = Inställningar
+appearance = Utseende
+play-animations = Spela animationer
+play-animations-description = Animera profilbilder och anpassade emojis. När det är av visas deras statiska versioner.
+desktop-notifications = Skrivbordsaviseringar
+mentions = Omnämnanden
+boosts = Boostar
+favourites = Favoriter
+follows = Följare och följförfrågningar
+polls = Avslutade omröstningar
+statuses = Inlägg från konton du får aviseringar om
+edits = Ändringar av inlägg du boostat
+admin = Registreringar och anmälningar
+request-moderation = Begär modereringsåtkomst
+request-moderation-description = Be om de administratörsbehörigheter moderatorer behöver vid inloggning. Logga in igen för att ändringen ska gälla.
+keyboard-shortcuts = Kortkommandon
+shortcut-next = Nästa inlägg
+shortcut-previous = Föregående inlägg
+shortcut-favourite = Favoritmarkera
+shortcut-boost = Knuffa
+shortcut-reply = Svara
+shortcut-compose = Nytt inlägg
+shortcut-open = Öppna tråd
+shortcut-home = Gå till hem
+shortcut-notifications = Gå till aviseringar
+
+## Aviseringar
+notification-mention = { $name } nämnde dig
+notification-reblog = { $others ->
+    [0] { $name } boostade ditt inlägg
+    [one] { $name } och 1 annan boostade ditt inlägg
+   *[other] { $name } och { $others } andra boostade ditt inlägg
+}
+notification-favourite = { $others ->
+    [0] { $name } favoritmarkerade ditt inlägg
+    [one] { $name } och 1 annan favoritmarkerade ditt inlägg
+   *[other] { $name } och { $others } andra favoritmarkerade ditt inlägg
+}
+notification-follow = { $name } följer dig
+notification-follow-request = { $name } vill följa dig
+notification-poll = En omröstning har avslutats
+notification-status = { $name } gjorde ett inlägg
+notification-update = { $name } ändrade ett inlägg
+notification-sign-up = { $name } registrerade sig
+notification-report = En ny anmälan har skickats in
+all = Alla
+filter-mentions = Omnämnanden
+filter-boosts = Boostar
+filter-favourites = Favoriter
+filter-follows = Följare
+filter-polls = Omröstningar
+filter-edits = Ändringar
+filter-admin = Administration
+
+## Mediavisare
+media-viewer = Mediavisare
+previous = Föregående
+next = Nästa
+play = Spela upp
+pause = Pausa
+open-original = Öppna original
+open-externally = Öppna externt
+close = Stäng
+no-description = Ingen beskrivning
+no-media = Ingen media
+
+## Fel
+retry = Försök igen
+error-auth = Din session är inte längre giltig. Logga in igen.
+error-rate-limited = Servern begränsar förfrågningar. Försök igen om några minuter.
+offline = Frånkopplad, { $queued ->
+    [one] 1 åtgärd i kö
+   *[other] { $queued } åtgärder i kö
+}
+pending = Väntar på att skickas
+error-rate-limited-until = Servern begränsar förfrågningar till { $time }.
+rate-limited-until = Hastighetsbegränsad till { $time }
+slowing-down-until = Saktar ner förfrågningar till { $time }
+error-offline = Du är frånkopplad. Åtgärder skickas när anslutningen är tillbaka.
+error-network = Kunde inte nå servern. Kontrollera din anslutning.
+error-login = Kunde inte logga in. Kontrollera serveradressen och försök igen.
+error-keyring = Kunde inte komma åt nyckelringen för att spara din inloggning.
+error-unknown = Något gick fel: { $reason }
+error-images = Vissa bilder kunde inte laddas.
+error-streaming = Liveuppdateringar är inte tillgängliga, tidslinjer uppdateras när de laddas om.
+error-not-moderator = Moderering kräver en inloggning med modereringsåtkomst, som kan begäras i inställningarna.
+error-schedule-time = Schemalagda tider skrivs som 2025-01-31 18:30.
+error-schedule-soon = Inlägg kan bara schemaläggas minst 5 minuter framåt.
@@ -0,0 +1,925 @@
+//! Requests against Mastodon endpoints that `mastodon-async` doesn't expose with the
+//! query parameters we need.
+
+use std::{cmp::Ordering, sync::LazyLock};
+
+use chrono::{DateTime, Utc};
+use mastodon_async::{
+    prelude::{Account, Notification, Status, StatusId, Visibility},
+    Mastodon, NewStatus,
+};
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::json;
+
+use crate::{error::Error, scheduler};
+
+/// Number of items requested per page.
+pub const PAGE_SIZE: usize = 20;
+
+/// Shortest duration of a poll the server takes, in seconds.
+const MIN_POLL_DURATION: i64 = 300;
+/// Duration of a poll posted again without an end, in seconds.
+const POLL_DURATION: i64 = 24 * 60 * 60;
+
+pub static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+/// Position of a requested page relative to the items that are already loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Pagination {
+    /// The most recent page.
+    Latest,
+    /// The page right below `max_id`.
+    Older { max_id: String },
+    /// The page right above `min_id`, bounded by `max_id`.
+    Between { max_id: String, min_id: String },
+}
+
+impl Pagination {
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("limit", PAGE_SIZE.to_string())];
+        match self {
+            Pagination::Latest => (),
+            Pagination::Older { max_id } => query.push(("max_id", max_id.clone())),
+            Pagination::Between { max_id, min_id } => {
+                query.push(("max_id", max_id.clone()));
+                query.push(("min_id", min_id.clone()));
+            }
+        }
+        query
+    }
+}
+
+/// The accounts found by a search.
+#[derive(Debug, Clone, Deserialize)]
+struct SearchResults {
+    accounts: Vec<Account>,
+}
+
+/// A timeline whose read position is synced between clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeline {
+    Home,
+    Notifications,
+}
+
+impl Timeline {
+    fn name(self) -> &'static str {
+        match self {
+            Timeline::Home => "home",
+            Timeline::Notifications => "notifications",
+        }
+    }
+}
+
+/// The last read position in a timeline.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Marker {
+    pub last_read_id: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Markers {
+    pub home: Option<Marker>,
+    pub notifications: Option<Marker>,
+}
+
+/// Uses of a trending hashtag or link on one day.
+#[derive(Debug, Clone, Deserialize)]
+pub struct History {
+    #[serde(deserialize_with = "number_from_string")]
+    pub uses: u64,
+    #[serde(deserialize_with = "number_from_string")]
+    pub accounts: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrendingTag {
+    pub name: String,
+    /// The last days, starting with today.
+    pub history: Vec<History>,
+}
+
+/// The preview card of a link shared in many statuses.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrendingLink {
+    pub url: Url,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub provider_name: String,
+    pub image: Option<String>,
+    /// The last days, starting with today.
+    pub history: Vec<History>,
+}
+
+/// A thread of direct messages.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    pub unread: bool,
+    /// The participants, without the user.
+    pub accounts: Vec<Account>,
+    pub last_status: Option<Status>,
+}
+
+/// A status the server posts at `scheduled_at`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduledStatus {
+    pub id: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub params: ScheduledParams,
+}
+
+/// The parameters a scheduled status is posted with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduledParams {
+    pub text: String,
+    pub spoiler_text: Option<String>,
+    pub visibility: Option<Visibility>,
+}
+
+/// The text a status was written with, before it was rendered to HTML.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusSource {
+    pub id: String,
+    pub text: String,
+    pub spoiler_text: String,
+    /// Taken from the status itself, the source endpoint only has the text.
+    #[serde(skip)]
+    pub content: StatusContent,
+}
+
+/// A deleted status, with its source text so it can be written again.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeletedStatus {
+    pub id: String,
+    #[serde(default)]
+    pub text: String,
+    pub spoiler_text: String,
+    pub in_reply_to_id: Option<String>,
+    pub visibility: Visibility,
+    #[serde(flatten)]
+    pub content: StatusContent,
+}
+
+/// What a status has besides its text, which an edit sends again so it's kept.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatusContent {
+    #[serde(default)]
+    media_attachments: Vec<MediaAttachment>,
+    pub poll: Option<PollSource>,
+    #[serde(default)]
+    pub sensitive: bool,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MediaAttachment {
+    id: String,
+}
+
+/// The poll of a status, enough to post it again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollSource {
+    options: Vec<PollOption>,
+    multiple: bool,
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    expired: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PollOption {
+    title: String,
+    /// Hidden while a poll that hides its totals runs.
+    votes_count: Option<u64>,
+}
+
+impl StatusContent {
+    /// The attachments, which the server keeps for a while after their status was deleted.
+    pub fn media_ids(&self) -> Vec<String> {
+        self.media_attachments
+            .iter()
+            .map(|attachment| attachment.id.clone())
+            .collect()
+    }
+
+    /// Adds the content to the body of a status request.
+    fn extend(&self, body: &mut serde_json::Value) {
+        body["media_ids"] = json!(self.media_ids());
+        body["sensitive"] = json!(self.sensitive);
+        if let Some(language) = &self.language {
+            body["language"] = json!(language);
+        }
+        if let Some(poll) = self.poll.as_ref().and_then(PollSource::body) {
+            body["poll"] = poll;
+        }
+    }
+}
+
+impl PollSource {
+    fn is_expired(&self) -> bool {
+        self.expired || self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Whether the votes are only counted once the poll ends, which the server doesn't
+    /// say but shows by leaving out the counts of a running poll.
+    fn hide_totals(&self) -> bool {
+        !self.is_expired()
+            && self
+                .options
+                .iter()
+                .all(|option| option.votes_count.is_none())
+    }
+
+    /// The poll as a status request sends it, ending when it would have ended. An ended
+    /// poll isn't sent, as that would open it again.
+    fn body(&self) -> Option<serde_json::Value> {
+        if self.is_expired() {
+            return None;
+        }
+        let expires_in = self
+            .expires_at
+            .map_or(POLL_DURATION, |at| (at - Utc::now()).num_seconds());
+        Some(json!({
+            "options": self.options.iter().map(|option| &option.title).collect::<Vec<_>>(),
+            "multiple": self.multiple,
+            "hide_totals": self.hide_totals(),
+            "expires_in": expires_in.max(MIN_POLL_DURATION),
+        }))
+    }
+}
+
+/// An account the server suggests following.
+#[derive(Debug, Clone, Deserialize)]
+struct Suggestion {
+    account: Account,
+}
+
+/// The application the access token was issued to.
+#[derive(Debug, Clone, Deserialize)]
+struct Application {
+    /// Missing on servers older than Mastodon 4.3.
+    #[serde(default)]
+    scopes: Option<Vec<String>>,
+}
+
+/// An account as moderators see it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminAccount {
+    pub id: String,
+    pub email: Option<String>,
+    /// Why the account asked to join, on servers that approve sign-ups.
+    pub invite_request: Option<String>,
+    pub account: Account,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Report {
+    pub id: String,
+    pub category: Option<String>,
+    pub comment: String,
+    /// The account that filed the report.
+    pub account: AdminAccount,
+    pub target_account: AdminAccount,
+    #[serde(default)]
+    pub statuses: Vec<Status>,
+}
+
+/// An action moderators take against a reported account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountAction {
+    Sensitive,
+    Silence,
+    Suspend,
+}
+
+impl AccountAction {
+    fn name(self) -> &'static str {
+        match self {
+            AccountAction::Sensitive => "sensitive",
+            AccountAction::Silence => "silence",
+            AccountAction::Suspend => "suspend",
+        }
+    }
+}
+
+/// Mastodon sends the counts of trend histories as strings.
+fn number_from_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Orders two Mastodon ids, which are numeric strings of varying length.
+pub fn compare_ids(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Turns error responses into errors, distinguishing rejected tokens and rate limits.
+pub fn check(response: Response) -> Result<Response, Error> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED => Err(Error::Auth(format!(
+            "{} rejected the access token",
+            response.url().host_str().unwrap_or_default()
+        ))),
+        StatusCode::TOO_MANY_REQUESTS => Err(Error::RateLimited {
+            reset: scheduler::reset(response.headers()),
+        }),
+        _ => Ok(response.error_for_status()?),
+    }
+}
+
+async fn respond(mastodon: &Mastodon, mut request: RequestBuilder) -> Result<Response, Error> {
+    if !mastodon.data.token.is_empty() {
+        request = request.bearer_auth(&mastodon.data.token);
+    }
+    check(scheduler::send(&CLIENT, request.build()?).await?)
+}
+
+async fn send<T: DeserializeOwned>(
+    mastodon: &Mastodon,
+    request: RequestBuilder,
+) -> Result<T, Error> {
+    Ok(respond(mastodon, request).await?.json().await?)
+}
+
+/// Like [`get`], also returning the `max_id` of the next page for lists that are only
+/// paginated through the `Link` header.
+async fn get_linked<T: DeserializeOwned>(
+    mastodon: &Mastodon,
+    endpoint: &str,
+    query: &[(&str, String)],
+) -> Result<(T, Option<String>), Error> {
+    let request = CLIENT
+        .get(format!("{}/api/{}", mastodon.data.base, endpoint))
+        .query(query);
+    let response = respond(mastodon, request).await?;
+    let next = response
+        .headers()
+        .get(reqwest::header::LINK)
+        .and_then(|link| link.to_str().ok())
+        .and_then(next_max_id);
+    Ok((response.json().await?, next))
+}
+
+/// The `max_id` of the `rel="next"` link in a `Link` header.
+fn next_max_id(link: &str) -> Option<String> {
+    let next = link.split(',').find(|link| link.contains("rel=\"next\""))?;
+    let url = next.trim().strip_prefix('<')?.split('>').next()?;
+    Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == "max_id")
+        .map(|(_, max_id)| max_id.into_owned())
+}
+
+pub async fn get<T: DeserializeOwned>(
+    mastodon: &Mastodon,
+    endpoint: &str,
+    query: &[(&str, String)],
+) -> Result<T, Error> {
+    let request = CLIENT
+        .get(format!("{}/api/{}", mastodon.data.base, endpoint))
+        .query(query);
+    send(mastodon, request).await
+}
+
+pub async fn post<T: DeserializeOwned>(
+    mastodon: &Mastodon,
+    endpoint: &str,
+    body: &impl Serialize,
+) -> Result<T, Error> {
+    let request = CLIENT
+        .post(format!("{}/api/{}", mastodon.data.base, endpoint))
+        .json(body);
+    send(mastodon, request).await
+}
+
+pub async fn put<T: DeserializeOwned>(
+    mastodon: &Mastodon,
+    endpoint: &str,
+    body: &impl Serialize,
+) -> Result<T, Error> {
+    let request = CLIENT
+        .put(format!("{}/api/{}", mastodon.data.base, endpoint))
+        .json(body);
+    send(mastodon, request).await
+}
+
+pub async fn delete<T: DeserializeOwned>(mastodon: &Mastodon, endpoint: &str) -> Result<T, Error> {
+    let request = CLIENT.delete(format!("{}/api/{}", mastodon.data.base, endpoint));
+    send(mastodon, request).await
+}
+
+/// Posts a status queued in the outbox. The server posts it only once per `key`, so
+/// sending it again after a response got lost doesn't post it twice.
+pub async fn post_status(
+    mastodon: &Mastodon,
+    body: &serde_json::Value,
+    key: &str,
+) -> Result<Status, Error> {
+    let request = CLIENT
+        .post(format!("{}/api/v1/statuses", mastodon.data.base))
+        .header("Idempotency-Key", key)
+        .json(body);
+    send(mastodon, request).await
+}
+
+pub async fn home_timeline(
+    mastodon: &Mastodon,
+    pagination: &Pagination,
+) -> Result<Vec<Status>, Error> {
+    get(mastodon, "v1/timelines/home", &pagination.query()).await
+}
+
+pub async fn public_timeline(
+    mastodon: &Mastodon,
+    local: bool,
+    remote: bool,
+    pagination: &Pagination,
+) -> Result<Vec<Status>, Error> {
+    let mut query = pagination.query();
+    query.push(("local", local.to_string()));
+    query.push(("remote", remote.to_string()));
+    get(mastodon, "v1/timelines/public", &query).await
+}
+
+pub async fn hashtag_timeline(
+    mastodon: &Mastodon,
+    hashtag: &str,
+    pagination: &Pagination,
+) -> Result<Vec<Status>, Error> {
+    get(
+        mastodon,
+        &format!("v1/timelines/tag/{}", hashtag),
+        &pagination.query(),
+    )
+    .await
+}
+
+/// Statuses gaining traction, `offset` statuses into the list.
+pub async fn trending_statuses(mastodon: &Mastodon, offset: usize) -> Result<Vec<Status>, Error> {
+    let query = [
+        ("limit", PAGE_SIZE.to_string()),
+        ("offset", offset.to_string()),
+    ];
+    get(mastodon, "v1/trends/statuses", &query).await
+}
+
+pub async fn trending_tags(mastodon: &Mastodon) -> Result<Vec<TrendingTag>, Error> {
+    get(
+        mastodon,
+        "v1/trends/tags",
+        &[("limit", PAGE_SIZE.to_string())],
+    )
+    .await
+}
+
+pub async fn trending_links(mastodon: &Mastodon) -> Result<Vec<TrendingLink>, Error> {
+    get(
+        mastodon,
+        "v1/trends/links",
+        &[("limit", PAGE_SIZE.to_string())],
+    )
+    .await
+}
+
+pub async fn account(mastodon: &Mastodon, id: &str) -> Result<Account, Error> {
+    get(mastodon, &format!("v1/accounts/{}", id), &[]).await
+}
+
+/// The account behind a profile url, which the server looks up on the account's home
+/// server if it doesn't know it yet.
+pub async fn resolve_account(mastodon: &Mastodon, url: &str) -> Result<Option<Account>, Error> {
+    let query = [
+        ("q", url.to_string()),
+        ("type", "accounts".to_string()),
+        ("resolve", "true".to_string()),
+        ("limit", "1".to_string()),
+    ];
+    let results: SearchResults = get(mastodon, "v2/search", &query).await?;
+    Ok(results.accounts.into_iter().next())
+}
+
+/// The logged-in account.
+pub async fn own_account(mastodon: &Mastodon) -> Result<Account, Error> {
+    get(mastodon, "v1/accounts/verify_credentials", &[]).await
+}
+
+pub async fn follow(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/accounts/{}/follow", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+pub async fn suggestions(mastodon: &Mastodon) -> Result<Vec<Account>, Error> {
+    let suggestions: Vec<Suggestion> =
+        get(mastodon, "v2/suggestions", &[("limit", "40".to_string())]).await?;
+    Ok(suggestions
+        .into_iter()
+        .map(|suggestion| suggestion.account)
+        .collect())
+}
+
+/// Stops suggesting the account with `id`.
+pub async fn dismiss_suggestion(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    delete::<serde_json::Value>(mastodon, &format!("v1/suggestions/{}", id)).await?;
+    Ok(())
+}
+
+/// Notifications of `types`, or of every type while it's empty.
+pub async fn notifications(
+    mastodon: &Mastodon,
+    pagination: &Pagination,
+    types: &[&str],
+) -> Result<Vec<Notification>, Error> {
+    let mut query = pagination.query();
+    query.extend(types.iter().map(|kind| ("types[]", kind.to_string())));
+    get(mastodon, "v1/notifications", &query).await
+}
+
+/// Conversations ordered by their last status, which `pagination` refers to.
+pub async fn conversations(
+    mastodon: &Mastodon,
+    pagination: &Pagination,
+) -> Result<Vec<Conversation>, Error> {
+    get(mastodon, "v1/conversations", &pagination.query()).await
+}
+
+pub async fn read_conversation(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/conversations/{}/read", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+pub async fn scheduled_statuses(mastodon: &Mastodon) -> Result<Vec<ScheduledStatus>, Error> {
+    get(
+        mastodon,
+        "v1/scheduled_statuses",
+        &[("limit", "40".to_string())],
+    )
+    .await
+}
+
+/// The body of a request posting `status`, with the poll of a redrafted status.
+pub fn status_body(
+    status: &NewStatus,
+    poll: Option<&PollSource>,
+) -> Result<serde_json::Value, Error> {
+    let mut body = serde_json::to_value(status)?;
+    if let Some(poll) = poll.and_then(PollSource::body) {
+        body["poll"] = poll;
+    }
+    Ok(body)
+}
+
+/// Has the server post `status` at `at` instead of right away.
+pub async fn schedule_status(
+    mastodon: &Mastodon,
+    status: &NewStatus,
+    poll: Option<&PollSource>,
+    at: DateTime<Utc>,
+) -> Result<ScheduledStatus, Error> {
+    let mut body = status_body(status, poll)?;
+    body["scheduled_at"] = json!(at.to_rfc3339());
+    post(mastodon, "v1/statuses", &body).await
+}
+
+pub async fn reschedule_status(
+    mastodon: &Mastodon,
+    id: &str,
+    at: DateTime<Utc>,
+) -> Result<ScheduledStatus, Error> {
+    let endpoint = format!("v1/scheduled_statuses/{}", id);
+    put(
+        mastodon,
+        &endpoint,
+        &json!({ "scheduled_at": at.to_rfc3339() }),
+    )
+    .await
+}
+
+pub async fn cancel_scheduled_status(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    delete::<serde_json::Value>(mastodon, &format!("v1/scheduled_statuses/{}", id)).await?;
+    Ok(())
+}
+
+/// Accounts asking to follow the user, with the `max_id` of the next page. Requests
+/// are paged by ids of their own, which only the `Link` header gives.
+pub async fn follow_requests(
+    mastodon: &Mastodon,
+    pagination: &Pagination,
+) -> Result<(Vec<Account>, Option<String>), Error> {
+    get_linked(mastodon, "v1/follow_requests", &pagination.query()).await
+}
+
+pub async fn authorize_follow_request(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/follow_requests/{}/authorize", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+pub async fn reject_follow_request(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/follow_requests/{}/reject", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+pub async fn favourite(mastodon: &Mastodon, id: &StatusId) -> Result<Status, Error> {
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/favourite", id),
+        &json!({}),
+    )
+    .await
+}
+
+pub async fn unfavourite(mastodon: &Mastodon, id: &StatusId) -> Result<Status, Error> {
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/unfavourite", id),
+        &json!({}),
+    )
+    .await
+}
+
+pub async fn reblog(mastodon: &Mastodon, id: &StatusId) -> Result<Status, Error> {
+    post(mastodon, &format!("v1/statuses/{}/reblog", id), &json!({})).await
+}
+
+pub async fn unreblog(mastodon: &Mastodon, id: &StatusId) -> Result<Status, Error> {
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/unreblog", id),
+        &json!({}),
+    )
+    .await
+}
+
+pub async fn bookmark(mastodon: &Mastodon, id: &StatusId) -> Result<Status, Error> {
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/bookmark", id),
+        &json!({}),
+    )
+    .await
+}
+
+pub async fn unbookmark(mastodon: &Mastodon, id: &StatusId) -> Result<Status, Error> {
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/unbookmark", id),
+        &json!({}),
+    )
+    .await
+}
+
+pub async fn status_source(mastodon: &Mastodon, id: &str) -> Result<StatusSource, Error> {
+    let mut source: StatusSource =
+        get(mastodon, &format!("v1/statuses/{}/source", id), &[]).await?;
+    source.content = get(mastodon, &format!("v1/statuses/{}", id), &[]).await?;
+    Ok(source)
+}
+
+/// Replaces the text of a status, keeping its attachments, poll and language.
+pub async fn edit_status(mastodon: &Mastodon, source: &StatusSource) -> Result<Status, Error> {
+    let mut body = json!({ "status": source.text, "spoiler_text": source.spoiler_text });
+    source.content.extend(&mut body);
+    put(mastodon, &format!("v1/statuses/{}", source.id), &body).await
+}
+
+pub async fn delete_status(mastodon: &Mastodon, id: &StatusId) -> Result<DeletedStatus, Error> {
+    delete(mastodon, &format!("v1/statuses/{}", id)).await
+}
+
+/// Pins the status to the profile, or unpins it when `pin` is `false`.
+pub async fn pin(mastodon: &Mastodon, id: &StatusId, pin: bool) -> Result<Status, Error> {
+    let action = if pin { "pin" } else { "unpin" };
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/{}", id, action),
+        &json!({}),
+    )
+    .await
+}
+
+/// Stops notifications from the thread of the status, or resumes them when `mute` is `false`.
+pub async fn mute_conversation(
+    mastodon: &Mastodon,
+    id: &StatusId,
+    mute: bool,
+) -> Result<Status, Error> {
+    let action = if mute { "mute" } else { "unmute" };
+    post(
+        mastodon,
+        &format!("v1/statuses/{}/{}", id, action),
+        &json!({}),
+    )
+    .await
+}
+
+pub async fn markers(mastodon: &Mastodon) -> Result<Markers, Error> {
+    let query = [Timeline::Home, Timeline::Notifications]
+        .map(|timeline| ("timeline[]", timeline.name().to_string()));
+    get(mastodon, "v1/markers", &query).await
+}
+
+pub async fn save_marker(
+    mastodon: &Mastodon,
+    timeline: Timeline,
+    last_read_id: &str,
+) -> Result<Markers, Error> {
+    let body = json!({ timeline.name(): { "last_read_id": last_read_id } });
+    post(mastodon, "v1/markers", &body).await
+}
+
+/// The scopes granted to the access token.
+/// The scopes granted to the access token, `None` on servers that don't list them.
+pub async fn scopes(mastodon: &Mastodon) -> Result<Option<Vec<String>>, Error> {
+    let application: Application = get(mastodon, "v1/apps/verify_credentials", &[]).await?;
+    Ok(application.scopes)
+}
+
+/// Whether the access token may read reports, for servers that don't list its scopes.
+pub async fn can_moderate(mastodon: &Mastodon) -> Result<bool, Error> {
+    let query = [("limit", "1".to_string())];
+    match get::<Vec<serde_json::Value>>(mastodon, "v1/admin/reports", &query).await {
+        Ok(_) => Ok(true),
+        Err(Error::Reqwest(err)) if err.status() == Some(StatusCode::FORBIDDEN) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+pub async fn reports(mastodon: &Mastodon) -> Result<Vec<Report>, Error> {
+    let query = [
+        ("resolved", "false".to_string()),
+        ("limit", "100".to_string()),
+    ];
+    get(mastodon, "v1/admin/reports", &query).await
+}
+
+pub async fn resolve_report(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/admin/reports/{}/resolve", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+/// Takes `action` against the account with `id`, which also resolves `report_id`.
+pub async fn moderate(
+    mastodon: &Mastodon,
+    id: &str,
+    action: AccountAction,
+    report_id: &str,
+) -> Result<(), Error> {
+    let endpoint = format!("v1/admin/accounts/{}/action", id);
+    let body = json!({ "type": action.name(), "report_id": report_id });
+    post::<serde_json::Value>(mastodon, &endpoint, &body).await?;
+    Ok(())
+}
+
+/// Local accounts waiting for their sign-up to be approved.
+pub async fn pending_accounts(mastodon: &Mastodon) -> Result<Vec<AdminAccount>, Error> {
+    let query = [
+        ("origin", "local".to_string()),
+        ("status", "pending".to_string()),
+        ("limit", "100".to_string()),
+    ];
+    get(mastodon, "v2/admin/accounts", &query).await
+}
+
+pub async fn approve_account(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/admin/accounts/{}/approve", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+pub async fn reject_account(mastodon: &Mastodon, id: &str) -> Result<(), Error> {
+    let endpoint = format!("v1/admin/accounts/{}/reject", id);
+    post::<serde_json::Value>(mastodon, &endpoint, &json!({})).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longer_ids_are_newer() {
+        assert_eq!(compare_ids("10", "9"), Ordering::Greater);
+        assert_eq!(compare_ids("99", "100"), Ordering::Less);
+        assert_eq!(
+            compare_ids("109876543210987654", "99999999999999999"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn ids_of_equal_length_compare_by_digits() {
+        assert_eq!(compare_ids("123", "124"), Ordering::Less);
+        assert_eq!(compare_ids("500", "499"), Ordering::Greater);
+        assert_eq!(compare_ids("42", "42"), Ordering::Equal);
+    }
+
+    #[test]
+    fn deleted_statuses_keep_their_content() {
+        let deleted: DeletedStatus = serde_json::from_value(json!({
+            "id": "1",
+            "text": "Which one?",
+            "spoiler_text": "",
+            "in_reply_to_id": null,
+            "visibility": "public",
+            "sensitive": true,
+            "language": "en",
+            "media_attachments": [{ "id": "7", "type": "image" }, { "id": "8", "type": "image" }],
+            "poll": {
+                "id": "3",
+                "options": [{ "title": "This", "votes_count": 2 }, { "title": "That" }],
+                "multiple": true,
+                "expires_at": null
+            }
+        }))
+        .unwrap();
+        assert_eq!(deleted.content.media_ids(), vec!["7", "8"]);
+
+        let mut body = json!({ "status": deleted.text });
+        deleted.content.extend(&mut body);
+        assert_eq!(body["status"], "Which one?");
+        assert_eq!(body["media_ids"], json!(["7", "8"]));
+        assert_eq!(body["sensitive"], true);
+        assert_eq!(body["language"], "en");
+        assert_eq!(body["poll"]["options"], json!(["This", "That"]));
+        assert_eq!(body["poll"]["multiple"], true);
+        assert_eq!(body["poll"]["hide_totals"], false);
+        assert_eq!(body["poll"]["expires_in"], POLL_DURATION);
+    }
+
+    #[test]
+    fn edits_without_attachments_clear_them() {
+        let mut body = json!({});
+        StatusContent::default().extend(&mut body);
+        assert_eq!(body["media_ids"], json!([]));
+        assert_eq!(body["sensitive"], false);
+        assert!(body.get("language").is_none());
+        assert!(body.get("poll").is_none());
+    }
+
+    fn poll(expires_in: chrono::Duration, votes_count: Option<u64>) -> PollSource {
+        PollSource {
+            options: vec![PollOption {
+                title: "Yes".to_string(),
+                votes_count,
+            }],
+            multiple: false,
+            expires_at: Some(Utc::now() + expires_in),
+            expired: false,
+        }
+    }
+
+    #[test]
+    fn running_polls_keep_their_end() {
+        let running = poll(chrono::Duration::hours(2), Some(1)).body().unwrap();
+        let expires_in = running["expires_in"].as_i64().unwrap();
+        assert!((2 * 60 * 60 - 5..=2 * 60 * 60).contains(&expires_in));
+
+        let ending = poll(chrono::Duration::minutes(1), Some(1)).body().unwrap();
+        assert_eq!(ending["expires_in"], MIN_POLL_DURATION);
+    }
+
+    #[test]
+    fn ended_polls_are_not_sent_again() {
+        let ended = poll(-chrono::Duration::hours(1), Some(1));
+        assert_eq!(ended.body(), None);
+        let closed = PollSource {
+            expired: true,
+            ..poll(chrono::Duration::hours(1), Some(1))
+        };
+        assert_eq!(closed.body(), None);
+
+        let mut body = json!({});
+        let content = StatusContent {
+            poll: Some(ended),
+            ..Default::default()
+        };
+        content.extend(&mut body);
+        assert!(body.get("poll").is_none());
+    }
+
+    #[test]
+    fn polls_without_counts_hide_their_totals() {
+        let hidden = poll(chrono::Duration::hours(1), None).body().unwrap();
+        assert_eq!(hidden["hide_totals"], true);
+        let shown = poll(chrono::Duration::hours(1), Some(0)).body().unwrap();
+        assert_eq!(shown["hide_totals"], false);
+    }
+
+    #[test]
+    fn the_next_page_is_read_from_the_link_header() {
+        let link = "<https://example.com/api/v1/follow_requests?max_id=7>; rel=\"next\", \
+                    <https://example.com/api/v1/follow_requests?min_id=9>; rel=\"prev\"";
+        assert_eq!(next_max_id(link), Some("7".to_string()));
+        let last = "<https://example.com/api/v1/follow_requests?min_id=9>; rel=\"prev\"";
+        assert_eq!(next_max_id(last), None);
+    }
+}
@@ -1,30 +1,46 @@
 // SPDX-License-Identifier: {{LICENSE}}
 
-use crate::config::TootConfig;
+use crate::config::{DesktopNotifications, TootConfig};
+use crate::error::Error;
+use crate::outbox::{self, Outbox};
 use crate::pages::public::TimelineType;
 use crate::pages::Page;
-use crate::utils::{self, Cache};
+use crate::shortcuts::{Action, Shortcuts};
+use crate::storage::{self, Draft, Snapshot};
+use crate::utils::{self, Cache, References};
+use crate::viewer::{self, Viewer};
 use crate::widgets::status::StatusOptions;
-use crate::{fl, pages, widgets};
+use crate::{api, desktop, fl, images, pages, scheduler, widgets};
+use chrono::{DateTime, Local, Utc};
 use cosmic::app::{context_drawer, Core, Task};
 use cosmic::cosmic_config;
 use cosmic::iced::alignment::{Horizontal, Vertical};
-use cosmic::iced::{Length, Subscription};
+use cosmic::iced::keyboard::{self, key::Named, Key, Modifiers};
+use cosmic::iced::time::Instant;
+use cosmic::iced::widget::scrollable::{self, RelativeOffset};
+use cosmic::iced::{event, window, Event, Length, Size, Subscription};
 use cosmic::widget::about::About;
 use cosmic::widget::image::Handle;
 use cosmic::widget::menu::{ItemHeight, ItemWidth};
+use cosmic::widget::toaster::{Toast, ToastId, Toasts};
 use cosmic::widget::{self, menu, nav_bar};
 use cosmic::{Application, ApplicationExt, Apply, Element};
 use mastodon_async::helpers::toml;
-use mastodon_async::prelude::{Account, Notification, Scopes, Status, StatusId};
+use mastodon_async::prelude::notification::Type as NotificationType;
+use mastodon_async::prelude::{
+    Account, Notification, NotificationId, Scopes, Status, StatusId, Visibility,
+};
 use mastodon_async::registration::Registered;
 use mastodon_async::{Data, Mastodon, NewStatus, Registration};
 use reqwest::Url;
 use std::collections::{HashMap, VecDeque};
 use std::str::FromStr;
+use std::time::Duration;
 
 const REPOSITORY: &str = "https://github.com/edfloreshz/toot";
 const SUPPORT: &str = "https://github.com/edfloreshz/toot/issues";
+/// Desktop notifications remembered for their actions.
+const DESKTOP_NOTIFICATION_LIMIT: usize = 100;
 
 pub struct AppModel {
     core: Core,
@@ -34,6 +50,10 @@ pub struct AppModel {
     key_binds: HashMap<menu::KeyBind, MenuAction>,
     dialog_pages: VecDeque<Dialog>,
     dialog_editor: widget::text_editor::Content,
+    /// When the post being composed is scheduled for, as typed.
+    dialog_schedule: String,
+    /// The poll of a redrafted status, posted again with it.
+    dialog_poll: Option<api::PollSource>,
     config: TootConfig,
     handler: Option<cosmic_config::Config>,
     instance: String,
@@ -41,11 +61,39 @@ pub struct AppModel {
     registration: Option<Registered>,
     mastodon: Mastodon,
     cache: Cache,
+    cache_dirty: bool,
+    started: Instant,
+    images: images::Loader,
     home: pages::home::Home,
     notifications: pages::notifications::Notifications,
-    explore: pages::public::Public,
+    follow_requests: pages::follow_requests::FollowRequests,
+    conversations: pages::conversations::Conversations,
+    scheduled: pages::scheduled::Scheduled,
+    suggestions: pages::suggestions::Suggestions,
+    moderation: pages::moderation::Moderation,
+    explore: pages::explore::Explore,
     local: pages::public::Public,
     federated: pages::public::Public,
+    /// The timeline of the last opened hashtag.
+    hashtag: Option<pages::public::Public>,
+    /// The media viewer window, while it's open.
+    viewer: Option<Viewer>,
+    toasts: Toasts<Message>,
+    /// Whether images fail to load, so only the first failure is reported.
+    images_failing: bool,
+    /// How requests are slowed down by the rate limit of the server.
+    rate_limited: Option<scheduler::Limited>,
+    /// Write actions that haven't reached the server yet.
+    outbox: Outbox,
+    /// Whether the first action of the outbox is being sent.
+    sending: bool,
+    /// Whether the server couldn't be reached the last time an action was sent.
+    offline: bool,
+    /// Notifications shown on the desktop by the id the notification service gave them.
+    desktop_notifications: HashMap<u32, Notification>,
+    /// Whether the access token can read reports, which lists the moderation page.
+    moderator: bool,
+    shortcuts: Shortcuts,
 }
 
 #[derive(Debug, Clone)]
@@ -61,18 +109,88 @@ pub enum Message {
     StoreRegistration(Option<Registered>),
     Home(pages::home::Message),
     Notifications(pages::notifications::Message),
-    Explore(pages::public::Message),
+    FollowRequests(pages::follow_requests::Message),
+    Conversations(pages::conversations::Message),
+    Scheduled(pages::scheduled::Message),
+    Suggestions(pages::suggestions::Message),
+    Moderation(pages::moderation::Message),
+    Explore(pages::explore::Message),
     Local(pages::public::Message),
     Federated(pages::public::Message),
+    Hashtag(pages::public::Message),
     Account(widgets::account::Message),
     Status(widgets::status::Message),
-    Fetch(Vec<Url>),
+    OpenAccount(String),
+    /// Opens the profile behind a url, such as a mention in a bio.
+    OpenAccountUrl(Url),
+    ShowAccount(Account),
+    OpenHashtag(String),
+    OpenMedia(StatusId, usize),
+    CloseViewer,
+    Viewer(viewer::Message),
+    ViewerKey(window::Id, Named),
+    Fetch(Vec<(Url, images::Thumbnail)>),
     CacheStatus(Status),
     CacheNotification(Notification),
     CacheHandle(Url, Handle),
+    CacheAnimation(Url, images::Animation),
+    ProfileAvatar(Url, Handle),
+    ImageFailed(Url, bool),
+    Restore(Snapshot),
+    SaveCache,
     Dialog(DialogAction),
     EditorAction(widget::text_editor::Action),
     UpdateMastodonInstance,
+    PlayAnimations(bool),
+    Tick(Instant),
+    PostStatus(NewStatus, Option<api::PollSource>),
+    EditSchedule(String),
+    /// Opens a draft in the composer with its poll and the time it was to be scheduled at.
+    OpenDraft(Draft),
+    /// Has the server post a status at a later time.
+    Schedule(NewStatus, Option<api::PollSource>, DateTime<Utc>),
+    /// Opens a status the server didn't schedule in the composer again, with the error.
+    ScheduleFailed(NewStatus, Option<api::PollSource>, DateTime<Utc>, String),
+    /// Shows an error, with a button that sends the message again if there is one.
+    Error(String, Option<Box<Message>>),
+    Retry(ToastId, Box<Message>),
+    CloseToast(ToastId),
+    RateLimited(Option<scheduler::Limited>),
+    RestoreOutbox(Outbox),
+    /// Queues a write action, sending it once the server can be reached.
+    Queue(outbox::Action),
+    /// Sends the first queued action.
+    Flush,
+    Sent(outbox::Action, Status),
+    SendFailed(outbox::Action, String, outbox::Failure),
+    SetDesktopNotifications(DesktopNotifications),
+    Notified(u32, Notification),
+    /// An action picked on a desktop notification, by notification id and action key.
+    DesktopAction(u32, String),
+    /// Accepts or rejects the follow request of an account by its id.
+    ResolveFollowRequest(String, bool),
+    FollowRequestResolved(String),
+    LoadMarkers,
+    Markers(api::Markers),
+    /// The number of unread entries of a page changed.
+    UnreadChanged,
+    SaveMarker(api::Timeline, String),
+    /// Asks for admin scopes on the next login.
+    RequestModeration(bool),
+    LoadScopes,
+    Scopes(Vec<String>),
+    LoadOwnAccount,
+    OwnAccount(Account),
+    EditStatus(api::StatusSource),
+    DeleteStatus(StatusId, bool),
+    StatusDeleted(api::DeletedStatus, bool),
+    OpenModeration,
+    /// An account was followed from the suggestions, by id.
+    Followed(String),
+    OpenPage(Page),
+    Key(window::Id, Modifiers, Key),
+    Shortcut(Action),
+    SetKeyBinding(Action, String),
     None,
 }
 
@@ -91,6 +209,11 @@ pub enum Dialog {
     Login(String),
     Code(String),
     Logout,
+    Edit(api::StatusSource),
+    /// Confirms deleting a status, which is written again in the composer when `true`.
+    Delete(StatusId, bool),
+    /// Confirms acting on the account reported in a report, by report id.
+    Moderate(String, api::AccountAction),
 }
 
 pub struct Flags {
@@ -117,26 +240,27 @@ impl Application for AppModel {
 
         let instance = instance(flags.config.server.clone());
 
-        let mastodon = match keytar::get_password(Self::APP_ID, "mastodon-data") {
-            Ok(data) => {
-                if data.success {
-                    let data: Data = toml::from_str(&data.password).unwrap();
-                    Mastodon::from(data)
-                } else {
-                    Mastodon::from(Data {
-                        base: instance.into(),
-                        ..Default::default()
-                    })
+        let mut errors = vec![];
+        let data = match keytar::get_password(Self::APP_ID, "mastodon-data") {
+            Ok(data) if data.success => match toml::from_str::<Data>(&data.password) {
+                Ok(data) => Some(data),
+                Err(err) => {
+                    tracing::error!("{err}");
+                    errors.push(Error::Keyring(err.to_string()));
+                    None
                 }
-            }
+            },
+            Ok(_) => None,
             Err(err) => {
                 tracing::error!("{err}");
-                Mastodon::from(Data {
-                    base: instance.into(),
-                    ..Default::default()
-                })
+                errors.push(Error::Keyring(err.to_string()));
+                None
             }
         };
+        let mastodon = Mastodon::from(data.unwrap_or_else(|| Data {
+            base: instance.into(),
+            ..Default::default()
+        }));
 
         let variants = mastodon
             .data
@@ -174,6 +298,8 @@ impl Application for AppModel {
             key_binds: HashMap::new(),
             dialog_pages: VecDeque::new(),
             dialog_editor: widget::text_editor::Content::default(),
+            dialog_schedule: String::new(),
+            dialog_poll: None,
             config: flags.config.clone(),
             handler: flags.handler,
             instance: flags.config.server,
@@ -181,16 +307,52 @@ impl Application for AppModel {
             registration: None,
             mastodon: mastodon.clone(),
             cache: Cache::new(),
+            cache_dirty: false,
+            started: Instant::now(),
+            images: images::Loader::default(),
             home: pages::home::Home::new(mastodon.clone()),
             notifications: pages::notifications::Notifications::new(mastodon.clone()),
-            explore: pages::public::Public::new(mastodon.clone(), TimelineType::Public),
+            follow_requests: pages::follow_requests::FollowRequests::new(mastodon.clone()),
+            conversations: pages::conversations::Conversations::new(mastodon.clone()),
+            scheduled: pages::scheduled::Scheduled::new(mastodon.clone()),
+            suggestions: pages::suggestions::Suggestions::new(mastodon.clone()),
+            moderation: pages::moderation::Moderation::new(mastodon.clone()),
+            explore: pages::explore::Explore::new(mastodon.clone()),
             local: pages::public::Public::new(mastodon.clone(), TimelineType::Local),
             federated: pages::public::Public::new(mastodon.clone(), TimelineType::Remote),
+            hashtag: None,
+            viewer: None,
+            toasts: Toasts::new(Message::CloseToast),
+            images_failing: false,
+            rate_limited: None,
+            outbox: Outbox::default(),
+            sending: false,
+            offline: false,
+            desktop_notifications: HashMap::new(),
+            moderator: false,
+            shortcuts: Shortcuts::new(&flags.config.key_bindings),
         };
 
         app.nav.activate_position(0);
+        app.cache.animate = !app.config.pause_animations;
 
-        let tasks = vec![app.update_title()];
+        let mut tasks = vec![
+            app.update_title(),
+            cosmic::task::future(async { Message::Restore(storage::load_snapshot().await) }),
+            cosmic::task::future(async { Message::RestoreOutbox(storage::load_outbox().await) }),
+            cosmic::task::future(async {
+                let drafts = storage::load_drafts().await;
+                Message::Scheduled(pages::scheduled::Message::RestoreDrafts(drafts))
+            }),
+        ];
+        if !app.mastodon.data.token.is_empty() {
+            tasks.push(app.update(Message::LoadMarkers));
+            tasks.push(app.update(Message::LoadScopes));
+            tasks.push(app.update(Message::LoadOwnAccount));
+        }
+        for err in errors {
+            tasks.push(app.update(Message::Error(err.user_message(), None)));
+        }
 
         (app, Task::batch(tasks))
     }
@@ -201,11 +363,18 @@ impl Application for AppModel {
             menu::root(fl!("view")),
             menu::items(
                 &self.key_binds,
-                vec![menu::Item::Button(
-                    fl!("about"),
-                    Some(widget::icon::from_name("help-info-symbolic").into()),
-                    MenuAction::About,
-                )],
+                vec![
+                    menu::Item::Button(
+                        fl!("settings"),
+                        Some(widget::icon::from_name("preferences-system-symbolic").into()),
+                        MenuAction::Settings,
+                    ),
+                    menu::Item::Button(
+                        fl!("about"),
+                        Some(widget::icon::from_name("help-info-symbolic").into()),
+                        MenuAction::About,
+                    ),
+                ],
             ),
         )])
         .item_height(ItemHeight::Dynamic(40))
@@ -216,7 +385,28 @@ impl Application for AppModel {
     }
 
     fn header_center(&self) -> Vec<Element<Self::Message>> {
-        vec![widget::text(self.instance.clone()).into()]
+        let spacing = cosmic::theme::active().cosmic().spacing;
+        let rate_limited = self.rate_limited.map(|limited| {
+            let time = limited
+                .until()
+                .with_timezone(&Local)
+                .format("%H:%M")
+                .to_string();
+            widget::text::caption(match limited {
+                scheduler::Limited::Throttled(_) => fl!("slowing-down-until", time = time),
+                scheduler::Limited::Blocked(_) => fl!("rate-limited-until", time = time),
+            })
+        });
+        let offline = self
+            .offline
+            .then(|| widget::text::caption(fl!("offline", queued = self.outbox.len())));
+        vec![widget::row()
+            .push(widget::text(self.instance.clone()))
+            .push_maybe(offline)
+            .push_maybe(rate_limited)
+            .align_y(Vertical::Center)
+            .spacing(spacing.space_xs)
+            .into()]
     }
 
     fn header_end(&self) -> Vec<Element<Self::Message>> {
@@ -250,22 +440,54 @@ impl Application for AppModel {
     fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<Self::Message> {
         self.nav.activate(id);
         let mut tasks = vec![];
-        match self.nav.data::<Page>(id).unwrap() {
-            Page::Home => tasks.push(
-                self.home
-                    .update(pages::home::Message::SetClient(self.mastodon.clone())),
-            ),
-            Page::Notifications => tasks.push(self.notifications.update(
-                pages::notifications::Message::SetClient(self.mastodon.clone()),
+        let Some(page) = self.nav.data::<Page>(id).cloned() else {
+            return Task::none();
+        };
+        match page {
+            Page::Home => {
+                tasks.push(
+                    self.home
+                        .update(pages::home::Message::SetClient(self.mastodon.clone())),
+                );
+                // Another client may have moved the read position meanwhile.
+                tasks.push(self.update(Message::LoadMarkers));
+            }
+            Page::Notifications => {
+                tasks.push(
+                    self.notifications
+                        .update(pages::notifications::Message::SetClient(
+                            self.mastodon.clone(),
+                        )),
+                );
+                tasks.push(self.update(Message::LoadMarkers));
+            }
+            Page::FollowRequests => tasks.push(self.follow_requests.update(
+                pages::follow_requests::Message::SetClient(self.mastodon.clone()),
+            )),
+            Page::Conversations => tasks.push(self.conversations.update(
+                pages::conversations::Message::SetClient(self.mastodon.clone()),
             )),
+            Page::Scheduled => tasks.push(
+                self.scheduled
+                    .update(pages::scheduled::Message::SetClient(self.mastodon.clone())),
+            ),
             Page::Search => (),
             Page::Favorites => (),
             Page::Bookmarks => (),
-            Page::Hashtags => (),
+            Page::Hashtags => {
+                if let Some(hashtag) = &mut self.hashtag {
+                    tasks.push(
+                        hashtag.update(pages::public::Message::SetClient(self.mastodon.clone())),
+                    )
+                }
+            }
             Page::Lists => (),
+            Page::Suggestions => tasks.push(self.suggestions.update(
+                pages::suggestions::Message::SetClient(self.mastodon.clone()),
+            )),
             Page::Explore => tasks.push(
                 self.explore
-                    .update(pages::public::Message::SetClient(self.mastodon.clone())),
+                    .update(pages::explore::Message::SetClient(self.mastodon.clone())),
             ),
             Page::Local => tasks.push(
                 self.local
@@ -275,7 +497,14 @@ impl Application for AppModel {
                 self.federated
                     .update(pages::public::Message::SetClient(self.mastodon.clone())),
             ),
+            Page::Moderation => tasks.push(
+                self.moderation
+                    .update(pages::moderation::Message::SetClient(self.mastodon.clone())),
+            ),
         };
+        self.evict();
+        // Evicted images of the page that is now visible need to be fetched again.
+        tasks.push(self.update(Message::Fetch(self.visible_images())));
         tasks.push(self.update_title());
         Task::batch(tasks)
     }
@@ -290,6 +519,10 @@ impl Application for AppModel {
                 context_drawer::about(&self.about, Message::Open, Message::ToggleContextDrawer)
                     .title(self.context_page.title())
             }
+            ContextPage::Settings => {
+                context_drawer::context_drawer(self.settings(), Message::ToggleContextDrawer)
+                    .title(self.context_page.title())
+            }
             ContextPage::Account(account) => {
                 context_drawer::context_drawer(self.account(account), Message::ToggleContextDrawer)
                     .title(self.context_page.title())
@@ -308,15 +541,23 @@ impl Application for AppModel {
 
         let dialog = match dialog_page {
             Dialog::Reply(new_status) => widget::dialog()
-                .title(fl!("reply"))
+                .title(match new_status.in_reply_to_id {
+                    Some(_) => fl!("reply"),
+                    None => fl!("new-post"),
+                })
                 .control(
                     widget::container(
                         widget::scrollable(
                             widget::column()
                                 .push_maybe(
-                                    self.cache
-                                        .statuses
-                                        .get(&new_status.in_reply_to_id.clone().unwrap())
+                                    matches!(new_status.visibility, Some(Visibility::Direct))
+                                        .then(|| widget::text::caption(fl!("direct-reply"))),
+                                )
+                                .push_maybe(
+                                    new_status
+                                        .in_reply_to_id
+                                        .as_ref()
+                                        .and_then(|id| self.cache.statuses.get(id))
                                         .map(|status| {
                                             widgets::status(
                                                 status,
@@ -334,6 +575,20 @@ impl Application for AppModel {
                                         .padding(spacing.space_s)
                                         .on_action(Message::EditorAction),
                                 )
+                                .push(
+                                    widget::row()
+                                        .push(widget::text::body(fl!("schedule-for")))
+                                        .push(
+                                            widget::text_input(
+                                                pages::scheduled::TIME_FORMAT,
+                                                &self.dialog_schedule,
+                                            )
+                                            .on_input(Message::EditSchedule)
+                                            .width(160),
+                                        )
+                                        .align_y(Vertical::Center)
+                                        .spacing(spacing.space_xs),
+                                )
                                 .spacing(spacing.space_xs),
                         )
                         .width(Length::Fill),
@@ -342,8 +597,14 @@ impl Application for AppModel {
                     .width(Length::Fill),
                 )
                 .primary_action(
-                    widget::button::suggested(fl!("reply"))
-                        .on_press_maybe(Some(Message::Dialog(DialogAction::Complete))),
+                    widget::button::suggested(
+                        match (&new_status.in_reply_to_id, self.dialog_schedule.is_empty()) {
+                            (_, false) => fl!("schedule"),
+                            (Some(_), true) => fl!("reply"),
+                            (None, true) => fl!("post"),
+                        },
+                    )
+                    .on_press_maybe(Some(Message::Dialog(DialogAction::Complete))),
                 )
                 .secondary_action(
                     widget::button::standard(fl!("cancel"))
@@ -353,13 +614,89 @@ impl Application for AppModel {
             Dialog::Login(instance) => self.login(instance.clone()),
             Dialog::Code(code) => self.code(code.clone()),
             Dialog::Logout => self.logout(),
+            Dialog::Edit(_) => widget::dialog()
+                .title(fl!("edit-post"))
+                .control(
+                    widget::text_editor(&self.dialog_editor)
+                        .height(200.)
+                        .padding(spacing.space_s)
+                        .on_action(Message::EditorAction),
+                )
+                .primary_action(
+                    widget::button::suggested(fl!("save"))
+                        .on_press(Message::Dialog(DialogAction::Complete)),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel"))
+                        .on_press(Message::Dialog(DialogAction::Close)),
+                ),
+            Dialog::Delete(_, redraft) => widget::dialog()
+                .title(fl!("delete-question"))
+                .body(if *redraft {
+                    fl!("redraft-description")
+                } else {
+                    fl!("delete-description")
+                })
+                .icon(widget::icon::from_name("edit-delete-symbolic"))
+                .primary_action(
+                    widget::button::destructive(fl!("delete"))
+                        .on_press(Message::Dialog(DialogAction::Complete)),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel"))
+                        .on_press(Message::Dialog(DialogAction::Close)),
+                ),
+            Dialog::Moderate(_, action) => {
+                let (title, body, label) = match action {
+                    api::AccountAction::Sensitive => (
+                        fl!("mark-sensitive-question"),
+                        fl!("mark-sensitive-description"),
+                        fl!("mark-sensitive"),
+                    ),
+                    api::AccountAction::Silence => (
+                        fl!("silence-question"),
+                        fl!("silence-description"),
+                        fl!("silence"),
+                    ),
+                    api::AccountAction::Suspend => (
+                        fl!("suspend-question"),
+                        fl!("suspend-description"),
+                        fl!("suspend"),
+                    ),
+                };
+                widget::dialog()
+                    .title(title)
+                    .body(body)
+                    .icon(widget::icon::from_name("dialog-warning-symbolic"))
+                    .primary_action(
+                        widget::button::destructive(label)
+                            .on_press(Message::Dialog(DialogAction::Complete)),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::Dialog(DialogAction::Close)),
+                    )
+            }
         };
 
         Some(dialog.into())
     }
 
+    fn on_close_requested(&self, id: window::Id) -> Option<Self::Message> {
+        self.viewer
+            .as_ref()
+            .filter(|viewer| viewer.window == id)
+            .map(|_| Message::CloseViewer)
+    }
+
     fn on_escape(&mut self) -> Task<Self::Message> {
-        if self.dialog_pages.pop_front().is_some() {
+        if let Some(dialog) = self.dialog_pages.pop_front() {
+            if let Dialog::Reply(mut new_status) = dialog {
+                new_status.status = Some(self.dialog_editor.text());
+                let poll = self.dialog_poll.take();
+                let schedule = std::mem::take(&mut self.dialog_schedule);
+                return self.save_draft(new_status, poll, schedule);
+            }
             return Task::none();
         }
 
@@ -378,9 +715,24 @@ impl Application for AppModel {
                     .notifications
                     .view(&self.cache)
                     .map(Message::Notifications),
+                Page::FollowRequests => self
+                    .follow_requests
+                    .view(&self.cache)
+                    .map(Message::FollowRequests),
+                Page::Conversations => self
+                    .conversations
+                    .view(&self.cache)
+                    .map(Message::Conversations),
+                Page::Scheduled => self.scheduled.view().map(Message::Scheduled),
+                Page::Suggestions => self.suggestions.view(&self.cache).map(Message::Suggestions),
                 Page::Explore => self.explore.view(&self.cache).map(Message::Explore),
                 Page::Local => self.local.view(&self.cache).map(Message::Local),
                 Page::Federated => self.federated.view(&self.cache).map(Message::Federated),
+                Page::Moderation => self.moderation.view(&self.cache).map(Message::Moderation),
+                Page::Hashtags => match &self.hashtag {
+                    Some(hashtag) => hashtag.view(&self.cache).map(Message::Hashtag),
+                    None => widget::text(fl!("no-hashtag")).into(),
+                },
                 _ => widget::text("Not yet implemented").into(),
             },
             None => widget::text("Select a page").into(),
@@ -390,9 +742,17 @@ impl Application for AppModel {
         .height(Length::Fill)
         .align_x(Horizontal::Center)
         .align_y(Vertical::Center)
+        .apply(|content| widget::toaster(&self.toasts, content))
         .into()
     }
 
+    fn view_window(&self, id: window::Id) -> Element<Self::Message> {
+        match &self.viewer {
+            Some(viewer) if viewer.window == id => viewer.view(&self.cache).map(Message::Viewer),
+            _ => widget::text("").into(),
+        }
+    }
+
     fn subscription(&self) -> Subscription<Self::Message> {
         let mut subscriptions = vec![self
             .core()
@@ -406,11 +766,31 @@ impl Application for AppModel {
                     .subscription()
                     .map(Message::Notifications),
             ),
+            Some(Page::FollowRequests) => subscriptions.push(
+                self.follow_requests
+                    .subscription()
+                    .map(Message::FollowRequests),
+            ),
+            Some(Page::Conversations) => subscriptions.push(
+                self.conversations
+                    .subscription()
+                    .map(Message::Conversations),
+            ),
+            Some(Page::Scheduled) => {
+                subscriptions.push(self.scheduled.subscription().map(Message::Scheduled))
+            }
             Some(Page::Search) => (),
             Some(Page::Favorites) => (),
             Some(Page::Bookmarks) => (),
-            Some(Page::Hashtags) => (),
+            Some(Page::Hashtags) => {
+                if let Some(hashtag) = &self.hashtag {
+                    subscriptions.push(hashtag.subscription().map(Message::Hashtag))
+                }
+            }
             Some(Page::Lists) => (),
+            Some(Page::Suggestions) => {
+                subscriptions.push(self.suggestions.subscription().map(Message::Suggestions))
+            }
             Some(Page::Explore) => {
                 subscriptions.push(self.explore.subscription().map(Message::Explore))
             }
@@ -418,6 +798,9 @@ impl Application for AppModel {
             Some(Page::Federated) => {
                 subscriptions.push(self.federated.subscription().map(Message::Federated))
             }
+            Some(Page::Moderation) => {
+                subscriptions.push(self.moderation.subscription().map(Message::Moderation))
+            }
             None => (),
         };
 
@@ -427,6 +810,54 @@ impl Application for AppModel {
             ));
         }
 
+        if self.viewer.is_some() {
+            subscriptions.push(event::listen_with(|event, status, id| {
+                match (event, status) {
+                    (
+                        Event::Keyboard(keyboard::Event::KeyPressed {
+                            key: Key::Named(key),
+                            ..
+                        }),
+                        event::Status::Ignored,
+                    ) => Some(Message::ViewerKey(id, key)),
+                    _ => None,
+                }
+            }));
+        }
+
+        subscriptions.push(event::listen_with(|event, status, id| {
+            match (event, status) {
+                (
+                    Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }),
+                    event::Status::Ignored,
+                ) => Some(Message::Key(id, modifiers, key)),
+                _ => None,
+            }
+        }));
+
+        if self.animating() {
+            subscriptions
+                .push(cosmic::iced::time::every(Duration::from_millis(50)).map(Message::Tick));
+        }
+
+        subscriptions.push(crate::subscriptions::rate_limit());
+
+        // Queued actions are sent again until the server takes them.
+        if !self.outbox.is_empty() && !self.sending {
+            subscriptions
+                .push(cosmic::iced::time::every(Duration::from_secs(30)).map(|_| Message::Flush));
+        }
+
+        if !self.mastodon.data.token.is_empty() {
+            subscriptions.push(desktop::actions());
+        }
+
+        if self.cache_dirty {
+            subscriptions.push(
+                cosmic::iced::time::every(Duration::from_secs(30)).map(|_| Message::SaveCache),
+            );
+        }
+
         Subscription::batch(subscriptions)
     }
 
@@ -434,9 +865,33 @@ impl Application for AppModel {
         let mut tasks = vec![];
         match message {
             Message::Home(message) => {
+                if let pages::home::Message::PrependStatus(status) = &message {
+                    // Direct messages also reach the home stream, their thread has news.
+                    if status.visibility == Visibility::Direct {
+                        tasks.push(
+                            self.conversations
+                                .update(pages::conversations::Message::Refresh),
+                        );
+                    }
+                }
                 tasks.push(self.home.update(message));
             }
             Message::Notifications(message) => {
+                if let pages::notifications::Message::PrependNotification(notification) = &message {
+                    let enabled = &self.config.desktop_notifications;
+                    if enabled.enabled(&notification.notification_type) {
+                        let notification = notification.clone();
+                        tasks.push(cosmic::task::future(async move {
+                            match desktop::notify(&notification).await {
+                                Ok(id) => Message::Notified(id, notification),
+                                Err(err) => {
+                                    tracing::error!("Failed to show notification: {}", err);
+                                    Message::None
+                                }
+                            }
+                        }));
+                    }
+                }
                 tasks.push(self.notifications.update(message));
             }
             Message::Explore(message) => {
@@ -448,74 +903,303 @@ impl Application for AppModel {
             Message::Federated(message) => {
                 tasks.push(self.federated.update(message));
             }
+            Message::FollowRequests(message) => {
+                tasks.push(self.follow_requests.update(message));
+            }
+            Message::Conversations(message) => {
+                tasks.push(self.conversations.update(message));
+            }
+            Message::Scheduled(message) => {
+                tasks.push(self.scheduled.update(message));
+            }
+            Message::Suggestions(message) => {
+                tasks.push(self.suggestions.update(message));
+            }
+            Message::Moderation(message) => {
+                tasks.push(self.moderation.update(message));
+            }
+            Message::Hashtag(message) => {
+                if let Some(hashtag) = &mut self.hashtag {
+                    tasks.push(hashtag.update(message));
+                }
+            }
             Message::Account(message) => tasks.push(widgets::account::update(message)),
             Message::Status(message) => match message {
                 widgets::status::Message::Favorite(status_id, favorited) => {
+                    tasks.push(self.update(Message::Queue(outbox::Action::Favourite(
+                        status_id, !favorited,
+                    ))))
+                }
+                widgets::status::Message::Boost(status_id, boosted) => tasks
+                    .push(self.update(Message::Queue(outbox::Action::Boost(status_id, !boosted)))),
+                widgets::status::Message::Bookmark(status_id, bookmarked) => {
+                    tasks.push(self.update(Message::Queue(outbox::Action::Bookmark(
+                        status_id,
+                        !bookmarked,
+                    ))))
+                }
+                widgets::status::Message::Edit(id) => {
                     let mastodon = self.mastodon.clone();
                     tasks.push(cosmic::task::future(async move {
-                        let result = if favorited {
-                            mastodon.unfavourite(&status_id).await
-                        } else {
-                            mastodon.favourite(&status_id).await
-                        };
-                        match result {
+                        match api::status_source(&mastodon, &id.to_string()).await {
+                            Ok(source) => Message::Dialog(DialogAction::Open(Dialog::Edit(source))),
+                            Err(err) => {
+                                tracing::error!("{err}");
+                                let retry = Message::Status(widgets::status::Message::Edit(id));
+                                Message::Error(err.user_message(), Some(Box::new(retry)))
+                            }
+                        }
+                    }));
+                }
+                widgets::status::Message::Delete(id, redraft) => tasks.push(self.update(
+                    Message::Dialog(DialogAction::Open(Dialog::Delete(id, redraft))),
+                )),
+                widgets::status::Message::Pin(id, pinned) => {
+                    let mastodon = self.mastodon.clone();
+                    tasks.push(cosmic::task::future(async move {
+                        match api::pin(&mastodon, &id, !pinned).await {
                             Ok(status) => Message::CacheStatus(status),
                             Err(err) => {
                                 tracing::error!("{err}");
-                                Message::None
+                                let retry = widgets::status::Message::Pin(id, pinned);
+                                let retry = Some(Box::new(Message::Status(retry)));
+                                Message::Error(err.user_message(), retry)
                             }
                         }
-                    }))
+                    }));
                 }
-                widgets::status::Message::Boost(status_id, boosted) => {
+                widgets::status::Message::Mute(id, muted) => {
                     let mastodon = self.mastodon.clone();
                     tasks.push(cosmic::task::future(async move {
-                        let result = if boosted {
-                            mastodon.unreblog(&status_id).await
-                        } else {
-                            mastodon.reblog(&status_id).await
-                        };
-                        match result {
+                        match api::mute_conversation(&mastodon, &id, !muted).await {
                             Ok(status) => Message::CacheStatus(status),
                             Err(err) => {
                                 tracing::error!("{err}");
-                                Message::None
+                                let retry = widgets::status::Message::Mute(id, muted);
+                                let retry = Some(Box::new(Message::Status(retry)));
+                                Message::Error(err.user_message(), retry)
                             }
                         }
-                    }))
+                    }));
                 }
-                widgets::status::Message::OpenLink(_) => todo!(),
                 _ => tasks.push(widgets::status::update(message)),
             },
+            Message::OpenAccount(id) => match self.cache.accounts.get(&id) {
+                Some(account) => tasks.push(self.update(Message::ShowAccount(account.clone()))),
+                None => {
+                    let mastodon = self.mastodon.clone();
+                    tasks.push(cosmic::task::future(async move {
+                        match api::account(&mastodon, &id).await {
+                            Ok(account) => Message::ShowAccount(account),
+                            Err(err) => {
+                                tracing::error!("{err}");
+                                Message::Error(
+                                    err.user_message(),
+                                    Some(Box::new(Message::OpenAccount(id))),
+                                )
+                            }
+                        }
+                    }));
+                }
+            },
+            Message::OpenAccountUrl(url) => {
+                let known = self
+                    .cache
+                    .accounts
+                    .values()
+                    .find(|account| account.url == url)
+                    .cloned();
+                match known {
+                    Some(account) => tasks.push(self.update(Message::ShowAccount(account))),
+                    None => {
+                        let mastodon = self.mastodon.clone();
+                        tasks.push(cosmic::task::future(async move {
+                            match api::resolve_account(&mastodon, url.as_str()).await {
+                                Ok(Some(account)) => Message::ShowAccount(account),
+                                // Not an account after all, so it's opened like other links.
+                                Ok(None) => Message::Open(url.to_string()),
+                                Err(err) => {
+                                    tracing::error!("{err}");
+                                    Message::Error(
+                                        err.user_message(),
+                                        Some(Box::new(Message::OpenAccountUrl(url))),
+                                    )
+                                }
+                            }
+                        }));
+                    }
+                }
+            }
+            Message::ShowAccount(account) => {
+                tasks.push(self.update(Message::Fetch(utils::extract_account_images(&account))));
+                tasks.push(self.load_profile_avatar(&account));
+                self.context_page = ContextPage::Account(account);
+                self.core.window.show_context = true;
+            }
+            Message::OpenHashtag(hashtag) => {
+                self.hashtag = Some(pages::public::Public::new(
+                    self.mastodon.clone(),
+                    TimelineType::Hashtag(hashtag),
+                ));
+                if let Some(id) = self.nav_entry(&Page::Hashtags) {
+                    tasks.push(self.on_nav_select(id));
+                }
+            }
+            Message::OpenMedia(id, index) => {
+                if let Some(status) = self.cache.statuses.get(&id.to_string()) {
+                    let attachments = status.media_attachments.clone();
+                    match &mut self.viewer {
+                        Some(viewer) => {
+                            let window = viewer.window;
+                            *viewer = Viewer::new(window, attachments, index);
+                            tasks.push(window::gain_focus(window));
+                        }
+                        None => {
+                            let (window, open) = window::open(window::Settings {
+                                size: Size::new(1024.0, 768.0),
+                                min_size: Some(Size::new(360.0, 240.0)),
+                                exit_on_close_request: false,
+                                ..Default::default()
+                            });
+                            self.viewer = Some(Viewer::new(window, attachments, index));
+                            tasks.push(open.map(|_| cosmic::app::Message::App(Message::None)));
+                            tasks.push(self.set_window_title(fl!("media-viewer"), window));
+                        }
+                    }
+                    if let Some(viewer) = &self.viewer {
+                        tasks.push(self.update(Message::Fetch(viewer.images())));
+                    }
+                }
+            }
+            Message::CloseViewer => {
+                if let Some(viewer) = self.viewer.take() {
+                    tasks.push(window::close(viewer.window));
+                }
+            }
+            Message::Viewer(message) => {
+                if let Some(viewer) = &mut self.viewer {
+                    tasks.push(viewer.update(message));
+                }
+            }
+            Message::ViewerKey(id, key) => {
+                let message = self
+                    .viewer
+                    .as_ref()
+                    .filter(|viewer| viewer.window == id)
+                    .and_then(|viewer| viewer.on_key(key));
+                if let Some(message) = message {
+                    tasks.push(self.update(Message::Viewer(message)));
+                }
+            }
             Message::CacheHandle(url, handle) => {
+                self.images_failing = false;
+                self.images.finish(&url, false);
                 self.cache.insert_handle(url.clone(), handle);
+                if self.cache.over_limit() {
+                    self.evict();
+                }
+                tasks.push(self.load_images());
+            }
+            Message::CacheAnimation(url, animation) => {
+                self.images_failing = false;
+                self.images.finish(&url, false);
+                self.cache.insert_animation(url, animation);
+                if self.cache.over_limit() {
+                    self.evict();
+                }
+                tasks.push(self.load_images());
+            }
+            Message::ProfileAvatar(url, handle) => {
+                self.cache.profile_avatar = Some((url, handle));
+            }
+            Message::ImageFailed(url, permanent) => {
+                self.images.finish(&url, permanent);
+                // Transient failures that outlasted the retries usually mean the
+                // connection is down, which only needs to be reported once.
+                if !permanent && !self.images_failing {
+                    self.images_failing = true;
+                    tasks.push(self.update(Message::Error(
+                        fl!("error-images"),
+                        Some(Box::new(Message::Fetch(self.visible_images()))),
+                    )));
+                }
+                tasks.push(self.load_images());
             }
             Message::CacheStatus(status) => {
                 self.cache.insert_status(status.clone());
+                // Server state that predates queued actions mustn't undo them on screen.
+                for action in self.outbox.iter() {
+                    self.cache.apply(action);
+                }
+                self.cache_dirty = true;
+                if self.cache.over_limit() {
+                    self.evict();
+                }
             }
             Message::CacheNotification(notification) => {
                 self.cache.insert_notification(notification.clone());
+                self.cache_dirty = true;
+                if self.cache.over_limit() {
+                    self.evict();
+                }
             }
-            Message::Fetch(urls) => {
-                for url in urls {
-                    if !self.cache.handles.contains_key(&url) {
-                        tasks.push(cosmic::task::future(async move {
-                            let result = match utils::get(&url).await {
-                                Ok(handle) => Some((url, handle)),
-                                Err(err) => {
-                                    tracing::error!("Failed to fetch image: {}", err);
-                                    None
-                                }
-                            };
-                            match result {
-                                Some((url, handle)) => {
-                                    Message::CacheHandle(url.clone(), handle.clone())
-                                }
-                                None => Message::None,
-                            }
-                        }));
-                    }
+            Message::Restore(snapshot) => {
+                // The snapshot lists the most recently seen accounts first.
+                for account in snapshot.accounts.into_iter().rev() {
+                    self.cache.insert_account(account);
                 }
+                if !self.mastodon.data.token.is_empty() {
+                    tasks.push(
+                        self.home
+                            .update(pages::home::Message::Restore(snapshot.home)),
+                    );
+                    tasks.push(
+                        self.notifications
+                            .update(pages::notifications::Message::Restore(
+                                snapshot.notifications,
+                            )),
+                    );
+                }
+                tasks.push(
+                    self.explore
+                        .update(pages::explore::Message::Restore(snapshot.explore)),
+                );
+                tasks.push(
+                    self.local
+                        .update(pages::public::Message::Restore(snapshot.local)),
+                );
+                tasks.push(
+                    self.federated
+                        .update(pages::public::Message::Restore(snapshot.federated)),
+                );
+            }
+            Message::SaveCache => {
+                self.cache_dirty = false;
+                let snapshot = self.snapshot();
+                tasks.push(cosmic::task::future(async move {
+                    if let Err(err) = storage::save_snapshot(snapshot).await {
+                        tracing::error!("Failed to save cache: {}", err);
+                    }
+                    if let Err(err) = storage::evict_images().await {
+                        tracing::error!("Failed to evict cached images: {}", err);
+                    }
+                    Message::None
+                }));
+            }
+            Message::Fetch(urls) => {
+                // Animated previews aren't part of the images of a loaded page, they're
+                // picked up here once their status is shown.
+                let urls: Vec<_> = urls
+                    .into_iter()
+                    .chain(self.visible_animations())
+                    .filter(|(url, thumbnail)| {
+                        !self.cache.handles.contains_key(url)
+                            && !self.cache.animations.contains_key(url)
+                            && (self.cache.animate || !thumbnail.is_animated())
+                    })
+                    .collect();
+                self.images.enqueue(urls);
+                tasks.push(self.load_images());
             }
             Message::InstanceEdit => {
                 let instance = self.instance.clone();
@@ -529,8 +1213,14 @@ impl Application for AppModel {
             }
             Message::RegisterMastodonClient => {
                 let mut registration = Registration::new(self.config.url());
+                let scopes = match registration_scopes(self.config.moderation) {
+                    Ok(scopes) => scopes,
+                    Err(err) => {
+                        tracing::error!("{err}");
+                        return self.update(Message::Error(err.user_message(), None));
+                    }
+                };
                 tasks.push(cosmic::task::future(async move {
-                    let scopes = Scopes::from_str("read write").unwrap();
                     match registration
                         .client_name("Toot")
                         .scopes(scopes)
@@ -540,7 +1230,10 @@ impl Application for AppModel {
                         Ok(registration) => Message::StoreRegistration(Some(registration)),
                         Err(err) => {
                             tracing::error!("{err}");
-                            Message::None
+                            Message::Error(
+                                Error::login(err).user_message(),
+                                Some(Box::new(Message::RegisterMastodonClient)),
+                            )
                         }
                     }
                 }));
@@ -556,14 +1249,18 @@ impl Application for AppModel {
                 self.registration = registration;
             }
             Message::CompleteRegistration => {
-                if let Some(registration) = self.registration.take() {
+                // Kept until the login succeeds, so a failed attempt can be retried.
+                if let Some(registration) = self.registration.clone() {
                     let code = self.code.clone();
                     let task = cosmic::task::future(async move {
                         match registration.complete(code).await {
                             Ok(mastodon) => Message::StoreMastodonData(mastodon),
                             Err(err) => {
                                 tracing::error!("{err}");
-                                Message::None
+                                Message::Error(
+                                    Error::login(err).user_message(),
+                                    Some(Box::new(Message::CompleteRegistration)),
+                                )
                             }
                         }
                     });
@@ -571,14 +1268,29 @@ impl Application for AppModel {
                 }
             }
             Message::StoreMastodonData(mastodon) => {
-                let data = &toml::to_string(&mastodon.data).unwrap();
-                match keytar::set_password(Self::APP_ID, "mastodon-data", data) {
+                let stored = toml::to_string(&mastodon.data)
+                    .map_err(|err| Error::Keyring(err.to_string()))
+                    .and_then(|data| {
+                        keytar::set_password(Self::APP_ID, "mastodon-data", &data)
+                            .map_err(|err| Error::Keyring(err.to_string()))
+                    });
+                match stored {
                     Ok(_) => {
+                        self.registration = None;
                         self.mastodon = mastodon;
+                        self.moderator = false;
                         self.update_navbar();
                         tasks.push(self.on_nav_select(self.nav.active()));
+                        tasks.push(self.update(Message::LoadScopes));
+                        tasks.push(self.update(Message::LoadOwnAccount));
+                    }
+                    Err(err) => {
+                        tracing::error!("{err}");
+                        tasks.push(self.update(Message::Error(
+                            err.user_message(),
+                            Some(Box::new(Message::StoreMastodonData(mastodon))),
+                        )));
                     }
-                    Err(err) => tracing::error!("{err}"),
                 }
             }
             Message::UpdateMastodonInstance => {
@@ -593,6 +1305,9 @@ impl Application for AppModel {
                 }
             }
             Message::ToggleContextPage(context_page) => {
+                if let ContextPage::Account(account) = &context_page {
+                    tasks.push(self.load_profile_avatar(account));
+                }
                 if self.context_page == context_page {
                     self.core.window.show_context = !self.core.window.show_context;
                 } else {
@@ -609,30 +1324,54 @@ impl Application for AppModel {
                         if let Some(status) = new_status.status.clone() {
                             self.dialog_editor = widget::text_editor::Content::with_text(&status);
                         }
+                        self.dialog_schedule.clear();
+                        self.dialog_poll = None;
                         self.dialog_pages.push_back(Dialog::Reply(new_status))
                     }
+                    Dialog::Edit(source) => {
+                        self.dialog_editor = widget::text_editor::Content::with_text(&source.text);
+                        self.dialog_pages.push_back(Dialog::Edit(source))
+                    }
                     _ => self.dialog_pages.push_back(dialog),
                 },
                 DialogAction::Update(dialog_page) => {
-                    self.dialog_pages[0] = dialog_page;
+                    if let Some(page) = self.dialog_pages.front_mut() {
+                        *page = dialog_page;
+                    }
                 }
                 DialogAction::Close => {
-                    self.dialog_pages.pop_front();
+                    if let Some(Dialog::Reply(mut new_status)) = self.dialog_pages.pop_front() {
+                        new_status.status = Some(self.dialog_editor.text());
+                        let poll = self.dialog_poll.take();
+                        let schedule = std::mem::take(&mut self.dialog_schedule);
+                        tasks.push(self.save_draft(new_status, poll, schedule));
+                    }
                 }
                 DialogAction::Complete => {
+                    let schedule = pages::scheduled::parse_time(&self.dialog_schedule);
+                    if let Some(Dialog::Reply(_)) = self.dialog_pages.front() {
+                        // The composer stays open so the time can be fixed.
+                        let message = match schedule {
+                            None if !self.dialog_schedule.trim().is_empty() => {
+                                Some(fl!("error-schedule-time"))
+                            }
+                            Some(at) if pages::scheduled::too_soon(at) => {
+                                Some(fl!("error-schedule-soon"))
+                            }
+                            _ => None,
+                        };
+                        if let Some(message) = message {
+                            return self.update(Message::Error(message, None));
+                        }
+                    }
                     if let Some(dialog_page) = self.dialog_pages.pop_front() {
                         match dialog_page {
                             Dialog::Reply(mut new_status) => {
                                 new_status.status = Some(self.dialog_editor.text());
-                                let mastodon = self.mastodon.clone();
-                                tasks.push(cosmic::task::future(async move {
-                                    match mastodon.new_status(new_status).await {
-                                        Ok(status) => Message::CacheStatus(status),
-                                        Err(err) => {
-                                            tracing::error!("{err}");
-                                            Message::None
-                                        }
-                                    }
+                                let poll = self.dialog_poll.take();
+                                tasks.push(self.update(match schedule {
+                                    Some(at) => Message::Schedule(new_status, poll, at),
+                                    None => Message::PostStatus(new_status, poll),
                                 }));
                             }
                             Dialog::SwitchInstance(instance) => {
@@ -652,17 +1391,46 @@ impl Application for AppModel {
                                 self.code = code;
                                 tasks.push(self.update(Message::CompleteRegistration))
                             }
+                            Dialog::Edit(mut source) => {
+                                source.text = self.dialog_editor.text();
+                                tasks.push(self.update(Message::EditStatus(source)));
+                            }
+                            Dialog::Delete(id, redraft) => {
+                                tasks.push(self.update(Message::DeleteStatus(id, redraft)))
+                            }
+                            Dialog::Moderate(id, action) => tasks.push(
+                                self.moderation
+                                    .update(pages::moderation::Message::Moderate(id, action)),
+                            ),
                             Dialog::Logout => {
                                 self.mastodon = Mastodon::from(Data {
                                     base: self.instance().into(),
                                     ..Default::default()
                                 });
+                                self.moderator = false;
                                 self.update_navbar();
+                                tasks.push(
+                                    self.moderation
+                                        .update(pages::moderation::Message::SetWritable(false)),
+                                );
+                                tasks.push(self.scheduled.update(pages::scheduled::Message::Clear));
+                                self.cache.clear();
+                                self.cache_dirty = false;
+                                self.outbox = Outbox::default();
+                                self.offline = false;
                                 if let Err(err) =
                                     keytar::delete_password(Self::APP_ID, "mastodon-data")
                                 {
                                     tracing::error!("{err}");
+                                    let message = Error::Keyring(err.to_string()).user_message();
+                                    tasks.push(self.update(Message::Error(message, None)));
                                 }
+                                tasks.push(cosmic::task::future(async {
+                                    if let Err(err) = storage::clear().await {
+                                        tracing::error!("{err}");
+                                    }
+                                    Message::None
+                                }));
                             }
                         }
                     }
@@ -672,8 +1440,563 @@ impl Application for AppModel {
                 self.dialog_editor.perform(action);
             }
             Message::UpdateConfig(config) => {
+                self.cache.animate = !config.pause_animations;
+                self.shortcuts = Shortcuts::new(&config.key_bindings);
                 self.config = config;
             }
+            Message::PlayAnimations(play) => {
+                if let Some(ref handler) = self.handler {
+                    match self.config.set_pause_animations(handler, !play) {
+                        Ok(true) => (),
+                        Ok(false) => tracing::error!("Failed to write config"),
+                        Err(err) => tracing::error!("{err}"),
+                    }
+                }
+                self.cache.animate = play;
+                // Animated avatars are skipped while animations are paused.
+                tasks.push(self.update(Message::Fetch(self.visible_images())));
+            }
+            Message::RateLimited(until) => self.rate_limited = until,
+            Message::Tick(instant) => {
+                self.cache.clock = instant.duration_since(self.started);
+            }
+            Message::PostStatus(new_status, poll) => {
+                match outbox::Action::post(&new_status, poll.as_ref()) {
+                    Ok(action) => tasks.push(self.update(Message::Queue(action))),
+                    Err(err) => {
+                        tracing::error!("{err}");
+                        tasks.push(self.update(Message::Error(err.user_message(), None)));
+                    }
+                }
+            }
+            Message::EditSchedule(schedule) => self.dialog_schedule = schedule,
+            Message::OpenDraft(draft) => {
+                tasks.push(
+                    self.update(Message::Dialog(DialogAction::Open(Dialog::Reply(
+                        draft.new_status(),
+                    )))),
+                );
+                self.dialog_schedule = draft.schedule;
+                self.dialog_poll = draft.poll;
+            }
+            Message::Schedule(new_status, poll, at) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::schedule_status(&mastodon, &new_status, poll.as_ref(), at).await {
+                        Ok(status) => Message::Scheduled(pages::scheduled::Message::Add(status)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            Message::ScheduleFailed(new_status, poll, at, err.user_message())
+                        }
+                    }
+                }));
+            }
+            Message::ScheduleFailed(new_status, poll, at, message) => {
+                // The composer is only taken over when it's free, otherwise the post
+                // becomes a draft.
+                if self.dialog_pages.is_empty() {
+                    tasks.push(
+                        self.update(Message::Dialog(DialogAction::Open(Dialog::Reply(
+                            new_status,
+                        )))),
+                    );
+                    self.dialog_schedule = pages::scheduled::format_time(&at);
+                    self.dialog_poll = poll;
+                } else {
+                    let schedule = pages::scheduled::format_time(&at);
+                    tasks.push(self.save_draft(new_status, poll, schedule));
+                }
+                tasks.push(self.update(Message::Error(message, None)));
+            }
+            Message::RestoreOutbox(restored) => {
+                // Actions taken before the outbox was loaded go after the restored ones.
+                let queued = std::mem::replace(&mut self.outbox, restored);
+                self.outbox.append(queued);
+                tasks.push(self.outbox_changed());
+                tasks.push(self.update(Message::Flush));
+            }
+            Message::Queue(action) => {
+                self.cache.apply(&action);
+                self.outbox.push(action, self.sending);
+                tasks.push(self.outbox_changed());
+                if !self.offline {
+                    tasks.push(self.update(Message::Flush));
+                }
+            }
+            Message::Flush => {
+                if let Some(action) = self.outbox.front().filter(|_| !self.sending).cloned() {
+                    self.sending = true;
+                    let mastodon = self.mastodon.clone();
+                    tasks.push(cosmic::task::future(async move {
+                        match action.send(&mastodon).await {
+                            Ok(status) => Message::Sent(action, status),
+                            Err(err) => {
+                                tracing::error!("{err}");
+                                let failure = outbox::Failure::from(&err);
+                                Message::SendFailed(action, err.user_message(), failure)
+                            }
+                        }
+                    }));
+                }
+            }
+            Message::Sent(action, status) => {
+                self.sending = false;
+                self.offline = false;
+                self.outbox.remove(&action);
+                tasks.push(self.outbox_changed());
+                tasks.push(self.update(Message::Fetch(utils::extract_status_images(&status))));
+                tasks.push(self.update(Message::CacheStatus(status)));
+                tasks.push(self.update(Message::Flush));
+            }
+            Message::SendFailed(action, message, failure) => {
+                self.sending = false;
+                match failure {
+                    outbox::Failure::Offline => {
+                        if !self.offline {
+                            self.offline = true;
+                            tasks.push(self.update(Message::Error(fl!("error-offline"), None)));
+                        }
+                    }
+                    outbox::Failure::Later => {
+                        tasks.push(self.update(Message::Error(message, None)));
+                    }
+                    outbox::Failure::Rejected => {
+                        self.outbox.remove(&action);
+                        if let Some(undo) = action.undo() {
+                            self.cache.apply(&undo);
+                        }
+                        tasks.push(self.outbox_changed());
+                        let retry = Some(Box::new(Message::Queue(action)));
+                        tasks.push(self.update(Message::Error(message, retry)));
+                        tasks.push(self.update(Message::Flush));
+                    }
+                }
+            }
+            Message::SetDesktopNotifications(desktop_notifications) => {
+                if let Some(ref handler) = self.handler {
+                    match self
+                        .config
+                        .set_desktop_notifications(handler, desktop_notifications)
+                    {
+                        Ok(true) => (),
+                        Ok(false) => tracing::error!("Failed to write config"),
+                        Err(err) => tracing::error!("{err}"),
+                    }
+                }
+            }
+            Message::Notified(id, notification) => {
+                // Ids only grow, so the oldest notification goes once enough were shown.
+                if self.desktop_notifications.len() >= DESKTOP_NOTIFICATION_LIMIT {
+                    if let Some(oldest) = self.desktop_notifications.keys().min().copied() {
+                        self.desktop_notifications.remove(&oldest);
+                    }
+                }
+                self.desktop_notifications.insert(id, notification);
+            }
+            Message::DesktopAction(id, action) => {
+                if let Some(notification) = self.desktop_notifications.remove(&id) {
+                    if let Some(window) = self.core.main_window_id() {
+                        tasks.push(window::gain_focus(window));
+                    }
+                    match (action.as_str(), notification.status) {
+                        (desktop::REPLY, Some(status)) => {
+                            tasks.push(self.update(Message::Status(
+                                widgets::status::Message::Reply(
+                                    status.id.clone(),
+                                    status.account.username.clone(),
+                                ),
+                            )));
+                        }
+                        (desktop::OPEN, Some(status)) => {
+                            let id = status.id.clone();
+                            tasks.push(self.update(Message::CacheStatus(status)));
+                            self.context_page = ContextPage::Status(id);
+                            self.core.window.show_context = true;
+                        }
+                        _ if matches!(
+                            notification.notification_type,
+                            NotificationType::SignUp | NotificationType::Report
+                        ) =>
+                        {
+                            tasks.push(self.update(Message::OpenModeration))
+                        }
+                        _ => tasks.push(self.update(Message::ShowAccount(notification.account))),
+                    }
+                }
+            }
+            Message::ResolveFollowRequest(id, accept) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    let result = if accept {
+                        api::authorize_follow_request(&mastodon, &id).await
+                    } else {
+                        api::reject_follow_request(&mastodon, &id).await
+                    };
+                    match result {
+                        Ok(()) => Message::FollowRequestResolved(id),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            Message::Error(
+                                err.user_message(),
+                                Some(Box::new(Message::ResolveFollowRequest(id, accept))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::FollowRequestResolved(id) => {
+                let resolved: Vec<NotificationId> = self
+                    .cache
+                    .notifications
+                    .values()
+                    .filter(|notification| {
+                        matches!(
+                            notification.notification_type,
+                            NotificationType::FollowRequest
+                        ) && notification.account.id.to_string() == id
+                    })
+                    .map(|notification| notification.id.clone())
+                    .collect();
+                for notification in &resolved {
+                    self.cache.notifications.remove(&notification.to_string());
+                }
+                self.cache_dirty = true;
+                tasks.push(
+                    self.notifications
+                        .update(pages::notifications::Message::RemoveNotifications(resolved)),
+                );
+                tasks.push(
+                    self.follow_requests
+                        .update(pages::follow_requests::Message::Remove(id)),
+                );
+            }
+            Message::LoadMarkers => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::markers(&mastodon).await {
+                        Ok(markers) => Message::Markers(markers),
+                        Err(err) => {
+                            tracing::error!("Failed to load read markers: {}", err);
+                            Message::None
+                        }
+                    }
+                }));
+            }
+            Message::Markers(markers) => {
+                if let Some(marker) = markers.home {
+                    tasks.push(
+                        self.home
+                            .update(pages::home::Message::SetLastRead(marker.last_read_id)),
+                    );
+                }
+                if let Some(marker) = markers.notifications {
+                    tasks.push(self.notifications.update(
+                        pages::notifications::Message::SetLastRead(marker.last_read_id),
+                    ));
+                }
+            }
+            Message::UnreadChanged => self.update_unread(),
+            Message::SaveMarker(timeline, last_read_id) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::save_marker(&mastodon, timeline, &last_read_id).await {
+                        Ok(_) => Message::None,
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            Message::Error(
+                                err.user_message(),
+                                Some(Box::new(Message::SaveMarker(timeline, last_read_id))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::RequestModeration(request) => {
+                if let Some(ref handler) = self.handler {
+                    match self.config.set_moderation(handler, request) {
+                        Ok(true) => (),
+                        Ok(false) => tracing::error!("Failed to write config"),
+                        Err(err) => tracing::error!("{err}"),
+                    }
+                }
+            }
+            Message::LoadScopes => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    let scopes = match api::scopes(&mastodon).await {
+                        Ok(Some(scopes)) => Ok(scopes),
+                        // The admin scopes are only ever requested together, so being able
+                        // to read reports means both were granted.
+                        Ok(None) => api::can_moderate(&mastodon).await.map(|moderator| {
+                            if moderator {
+                                vec!["admin:read".to_string(), "admin:write".to_string()]
+                            } else {
+                                vec![]
+                            }
+                        }),
+                        Err(err) => Err(err),
+                    };
+                    match scopes {
+                        Ok(scopes) => Message::Scopes(scopes),
+                        Err(err) => {
+                            tracing::error!("Failed to load token scopes: {}", err);
+                            Message::None
+                        }
+                    }
+                }));
+            }
+            Message::Scopes(scopes) => {
+                self.moderator = scopes.iter().any(|scope| scope.starts_with("admin:read"));
+                // Reports can be read with `admin:read` alone, acting on them takes `admin:write`.
+                let writable = scopes.iter().any(|scope| scope.starts_with("admin:write"));
+                tasks.push(
+                    self.moderation
+                        .update(pages::moderation::Message::SetWritable(writable)),
+                );
+                match (self.moderator, self.nav_entry(&Page::Moderation)) {
+                    (true, None) => {
+                        self.nav
+                            .insert()
+                            .text(Page::Moderation.to_string())
+                            .icon(widget::icon::from_name(Page::Moderation.icon()))
+                            .data::<Page>(Page::Moderation);
+                    }
+                    (false, Some(id)) => self.nav.remove(id),
+                    _ => (),
+                }
+            }
+            Message::LoadOwnAccount => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::own_account(&mastodon).await {
+                        Ok(account) => Message::OwnAccount(account),
+                        Err(err) => {
+                            tracing::error!("Failed to load the logged-in account: {}", err);
+                            Message::None
+                        }
+                    }
+                }));
+            }
+            Message::OwnAccount(account) => {
+                self.cache.me = Some(account.id.to_string());
+                self.cache.insert_account(account);
+            }
+            Message::EditStatus(source) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::edit_status(&mastodon, &source).await {
+                        Ok(status) => Message::CacheStatus(status),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            let retry = Message::EditStatus(source);
+                            Message::Error(err.user_message(), Some(Box::new(retry)))
+                        }
+                    }
+                }));
+            }
+            Message::DeleteStatus(id, redraft) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::delete_status(&mastodon, &id).await {
+                        Ok(deleted) => Message::StatusDeleted(deleted, redraft),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            let retry = Message::DeleteStatus(id, redraft);
+                            Message::Error(err.user_message(), Some(Box::new(retry)))
+                        }
+                    }
+                }));
+            }
+            Message::StatusDeleted(deleted, redraft) => {
+                let id = deleted.id.clone();
+                self.cache.statuses.remove(&id);
+                tasks.push(
+                    self.update(Message::Home(pages::home::Message::DeleteStatus(
+                        id.clone(),
+                    ))),
+                );
+                tasks.push(
+                    self.local
+                        .update(pages::public::Message::DeleteStatus(id.clone())),
+                );
+                tasks.push(
+                    self.federated
+                        .update(pages::public::Message::DeleteStatus(id.clone())),
+                );
+                if let Some(hashtag) = &mut self.hashtag {
+                    tasks.push(hashtag.update(pages::public::Message::DeleteStatus(id.clone())));
+                }
+                tasks.push(self.update(Message::Conversations(
+                    pages::conversations::Message::DeleteStatus(id.clone()),
+                )));
+                // Notifications about the status go with it.
+                let notifications: Vec<_> = self
+                    .cache
+                    .notifications
+                    .values()
+                    .filter(|notification| {
+                        notification
+                            .status
+                            .as_ref()
+                            .is_some_and(|status| status.id.to_string() == id)
+                    })
+                    .map(|notification| notification.id.clone())
+                    .collect();
+                if !notifications.is_empty() {
+                    for notification in &notifications {
+                        self.cache.notifications.remove(&notification.to_string());
+                    }
+                    tasks.push(self.update(Message::Notifications(
+                        pages::notifications::Message::RemoveNotifications(notifications),
+                    )));
+                }
+                self.cache_dirty = true;
+                // The server keeps the attachments for a while, so they're posted again.
+                if redraft {
+                    let media_ids = deleted.content.media_ids();
+                    let new_status = NewStatus {
+                        status: Some(deleted.text),
+                        in_reply_to_id: deleted.in_reply_to_id,
+                        media_ids: (!media_ids.is_empty()).then_some(media_ids),
+                        sensitive: Some(deleted.content.sensitive),
+                        spoiler_text: (!deleted.spoiler_text.is_empty())
+                            .then_some(deleted.spoiler_text),
+                        visibility: Some(deleted.visibility),
+                        ..Default::default()
+                    };
+                    tasks.push(
+                        self.update(Message::Dialog(DialogAction::Open(Dialog::Reply(
+                            new_status,
+                        )))),
+                    );
+                    self.dialog_poll = deleted.content.poll;
+                }
+            }
+            Message::OpenModeration => match self.nav_entry(&Page::Moderation) {
+                Some(id) => tasks.push(self.on_nav_select(id)),
+                None => tasks.push(self.update(Message::Error(fl!("error-not-moderator"), None))),
+            },
+            Message::Followed(id) => {
+                tasks.push(
+                    self.suggestions
+                        .update(pages::suggestions::Message::Remove(id)),
+                );
+                // The statuses of the followed account show up in the home timeline.
+                tasks.push(self.home.update(pages::home::Message::Refresh));
+            }
+            Message::OpenPage(page) => {
+                if let Some(id) = self.nav_entry(&page) {
+                    tasks.push(self.on_nav_select(id));
+                }
+            }
+            Message::Key(id, modifiers, key) => {
+                // Keys pressed in the media viewer or while a dialog is open aren't shortcuts.
+                let viewer = self.viewer.as_ref().is_some_and(|v| v.window == id);
+                if !viewer && self.dialog_pages.is_empty() {
+                    if let Some(action) = self.shortcuts.press(modifiers, key) {
+                        tasks.push(self.update(Message::Shortcut(action)));
+                    }
+                }
+            }
+            Message::Shortcut(action) => match action {
+                Action::Next | Action::Previous => {
+                    let ids: Vec<String> = self
+                        .page_status_ids()
+                        .into_iter()
+                        .map(ToString::to_string)
+                        .collect();
+                    let selected = self
+                        .cache
+                        .selected
+                        .as_ref()
+                        .and_then(|selected| ids.iter().position(|id| id == selected));
+                    let index = match (action, selected) {
+                        (Action::Next, Some(index)) => (index + 1).min(ids.len() - 1),
+                        (Action::Previous, Some(index)) => index.saturating_sub(1),
+                        _ => 0,
+                    };
+                    if let Some(id) = ids.get(index) {
+                        self.cache.selected = Some(id.clone());
+                        // Statuses differ in height, scrolling by position is close enough.
+                        let y = match ids.len() {
+                            1 => 0.0,
+                            len => index as f32 / (len - 1) as f32,
+                        };
+                        tasks.push(scrollable::snap_to(
+                            pages::SCROLLABLE.clone(),
+                            RelativeOffset { x: 0.0, y },
+                        ));
+                    }
+                }
+                Action::Favourite => {
+                    if let Some(status) = self.selected_status() {
+                        let favourited = status.favourited.unwrap_or_default();
+                        let message =
+                            widgets::status::Message::Favorite(status.id.clone(), favourited);
+                        tasks.push(self.update(Message::Status(message)));
+                    }
+                }
+                Action::Boost => {
+                    if let Some(status) = self.selected_status() {
+                        let boosted = status.reblogged.unwrap_or_default();
+                        let message = widgets::status::Message::Boost(status.id.clone(), boosted);
+                        tasks.push(self.update(Message::Status(message)));
+                    }
+                }
+                Action::Reply => {
+                    if let Some(status) = self.selected_status() {
+                        tasks.push(widgets::status::update(widgets::status::Message::Reply(
+                            status.id.clone(),
+                            status.account.username.clone(),
+                        )));
+                    }
+                }
+                Action::Compose => {
+                    let new_status = NewStatus {
+                        status: Some(String::new()),
+                        ..Default::default()
+                    };
+                    tasks.push(
+                        self.update(Message::Dialog(DialogAction::Open(Dialog::Reply(
+                            new_status,
+                        )))),
+                    );
+                }
+                Action::Open => {
+                    if let Some(id) = self.selected_status().map(|status| status.id.clone()) {
+                        self.context_page = ContextPage::Status(id);
+                        self.core.window.show_context = true;
+                    }
+                }
+                Action::Home => tasks.push(self.update(Message::OpenPage(Page::Home))),
+                Action::Notifications => {
+                    tasks.push(self.update(Message::OpenPage(Page::Notifications)))
+                }
+            },
+            Message::SetKeyBinding(action, keys) => {
+                let mut key_bindings = self.config.key_bindings.clone();
+                key_bindings.set(action, keys);
+                if let Some(ref handler) = self.handler {
+                    match self.config.set_key_bindings(handler, key_bindings) {
+                        Ok(true) => (),
+                        Ok(false) => tracing::error!("Failed to write config"),
+                        Err(err) => tracing::error!("{err}"),
+                    }
+                }
+                self.shortcuts = Shortcuts::new(&self.config.key_bindings);
+            }
+            Message::Error(message, retry) => {
+                let mut toast = Toast::new(message);
+                if let Some(retry) = retry {
+                    toast = toast.action(fl!("retry"), move |id| Message::Retry(id, retry.clone()));
+                }
+                tasks.push(self.toasts.push(toast).map(cosmic::app::Message::App));
+            }
+            Message::Retry(id, message) => {
+                self.toasts.remove(id);
+                tasks.push(self.update(*message));
+            }
+            Message::CloseToast(id) => self.toasts.remove(id),
             Message::None => (),
         }
         Task::batch(tasks)
@@ -681,11 +2004,41 @@ impl Application for AppModel {
 }
 
 impl AppModel {
+    /// Keeps a post that wasn't sent as a draft instead of losing it, unless it only
+    /// has the mentions a reply starts with.
+    fn save_draft(
+        &mut self,
+        new_status: NewStatus,
+        poll: Option<api::PollSource>,
+        schedule: String,
+    ) -> Task<Message> {
+        let draft = Draft::new(new_status, poll, schedule);
+        if draft.is_empty() {
+            return Task::none();
+        }
+        self.update(Message::Scheduled(pages::scheduled::Message::SaveDraft(
+            draft,
+        )))
+    }
+
+    /// Marks the statuses with queued actions and saves the outbox.
+    fn outbox_changed(&mut self) -> Task<Message> {
+        self.cache.pending = self.outbox.pending().collect();
+        let outbox = self.outbox.clone();
+        cosmic::task::future(async move {
+            if let Err(err) = storage::save_outbox(outbox).await {
+                tracing::error!("Failed to save queued actions: {}", err);
+            }
+            Message::None
+        })
+    }
+
     pub fn update_title(&mut self) -> Task<Message> {
         let mut window_title = fl!("app-title");
-        if let Some(page) = self.nav.text(self.nav.active()) {
+        // The navigation text may carry an unread count, the title keeps the plain name.
+        if let Some(page) = self.nav.active_data::<Page>() {
             window_title.push_str(" — ");
-            window_title.push_str(page);
+            window_title.push_str(&page.to_string());
         }
         if let Some(id) = self.core.main_window_id() {
             self.set_window_title(window_title, id)
@@ -788,9 +2141,372 @@ impl AppModel {
         widget::column().push_maybe(status).into()
     }
 
+    fn settings(&self) -> Element<Message> {
+        let enabled = self.config.desktop_notifications;
+        let toggler = move |label: String, value, set: fn(&mut DesktopNotifications, bool)| {
+            widget::settings::item::builder(label).toggler(value, move |value| {
+                let mut enabled = enabled;
+                set(&mut enabled, value);
+                Message::SetDesktopNotifications(enabled)
+            })
+        };
+
+        widget::settings::view_column(vec![
+            widget::settings::section()
+                .title(fl!("appearance"))
+                .add(
+                    widget::settings::item::builder(fl!("play-animations"))
+                        .description(fl!("play-animations-description"))
+                        .toggler(!self.config.pause_animations, Message::PlayAnimations),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("desktop-notifications"))
+                .add(toggler(fl!("mentions"), enabled.mentions, |n, v| {
+                    n.mentions = v
+                }))
+                .add(toggler(fl!("boosts"), enabled.boosts, |n, v| n.boosts = v))
+                .add(toggler(fl!("favourites"), enabled.favourites, |n, v| {
+                    n.favourites = v
+                }))
+                .add(toggler(fl!("follows"), enabled.follows, |n, v| {
+                    n.follows = v
+                }))
+                .add(toggler(fl!("polls"), enabled.polls, |n, v| n.polls = v))
+                .add(toggler(fl!("statuses"), enabled.statuses, |n, v| {
+                    n.statuses = v
+                }))
+                .add(toggler(fl!("edits"), enabled.edits, |n, v| n.edits = v))
+                .add(toggler(fl!("admin"), enabled.admin, |n, v| n.admin = v))
+                .into(),
+            widget::settings::section()
+                .title(fl!("moderation"))
+                .add(
+                    widget::settings::item::builder(fl!("request-moderation"))
+                        .description(fl!("request-moderation-description"))
+                        .toggler(self.config.moderation, Message::RequestModeration),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("keyboard-shortcuts"))
+                .extend(Action::ALL.into_iter().map(|action| {
+                    widget::settings::item::builder(action.label()).control(
+                        widget::text_input("", self.config.key_bindings.get(action))
+                            .on_input(move |keys| Message::SetKeyBinding(action, keys))
+                            .width(120),
+                    )
+                }))
+                .into(),
+        ])
+        .into()
+    }
+
     fn account<'a>(&'a self, account: &'a Account) -> Element<'a, Message> {
-        crate::widgets::account(account, &self.cache.handles).map(Message::Account)
+        crate::widgets::account(account, &self.cache).map(Message::Account)
+    }
+
+    /// Starts downloading queued images while the loader has free slots.
+    fn load_images(&mut self) -> Task<Message> {
+        let tasks: Vec<Task<Message>> = self
+            .images
+            .start()
+            .into_iter()
+            .map(|(url, thumbnail)| {
+                cosmic::task::future(async move {
+                    match images::fetch(&url, thumbnail).await {
+                        Ok(images::Image::Still(handle)) => Message::CacheHandle(url, handle),
+                        Ok(images::Image::Animated(animation)) => {
+                            Message::CacheAnimation(url, animation)
+                        }
+                        Err(err) => {
+                            tracing::error!("Failed to fetch image: {}", err);
+                            Message::ImageFailed(url, err.is_permanent())
+                        }
+                    }
+                })
+            })
+            .collect();
+        Task::batch(tasks)
+    }
+
+    /// Fetches the avatar of `account` at its size in the profile drawer.
+    fn load_profile_avatar(&self, account: &Account) -> Task<Message> {
+        let url = account.avatar_static.clone();
+        if self.cache.profile_avatar.as_ref().map(|(loaded, _)| loaded) == Some(&url) {
+            return Task::none();
+        }
+        cosmic::task::future(async move {
+            match images::fetch(&url, images::Thumbnail::ProfileAvatar).await {
+                Ok(images::Image::Still(handle)) => Message::ProfileAvatar(url, handle),
+                Ok(images::Image::Animated(_)) => Message::None,
+                Err(err) => {
+                    tracing::warn!("Failed to fetch profile avatar: {}", err);
+                    Message::None
+                }
+            }
+        })
+    }
+
+    /// Trims the pages and drops what no page references anymore, trimming the pages
+    /// that aren't visible further until the entries fit in [`utils::ENTRY_LOW_WATER`].
+    fn evict(&mut self) {
+        // Eviction for the images alone leaves the pages at their usual length.
+        let over = self.cache.entries() > utils::ENTRY_LIMIT;
+        let mut limit = pages::PAGE_LIMIT;
+        loop {
+            self.trim_pages(limit);
+            self.retain_references();
+            if !over || self.cache.entries() <= utils::ENTRY_LOW_WATER || limit == 0 {
+                break;
+            }
+            limit /= 2;
+        }
+        self.update_unread();
+    }
+
+    /// Trims the pages that aren't visible to `limit` entries, and the active page to
+    /// [`utils::ENTRY_LOW_WATER`] when it alone is over [`utils::ENTRY_LIMIT`].
+    fn trim_pages(&mut self, limit: usize) {
+        let active = self.nav.active_data::<Page>().cloned();
+        let limit_for = |page: Page, len: usize| {
+            if active.as_ref() != Some(&page) {
+                Some(limit)
+            } else if len > utils::ENTRY_LIMIT {
+                Some(utils::ENTRY_LOW_WATER)
+            } else {
+                None
+            }
+        };
+        if let Some(limit) = limit_for(Page::Home, self.home.status_ids().count()) {
+            self.home.trim(limit);
+        }
+        let len = self.notifications.notification_ids().count();
+        if let Some(limit) = limit_for(Page::Notifications, len) {
+            self.notifications.trim(limit);
+        }
+        let len = self.conversations.status_ids().count();
+        if let Some(limit) = limit_for(Page::Conversations, len) {
+            self.conversations.trim(limit);
+        }
+        if let Some(limit) = limit_for(Page::Explore, self.explore.status_ids().count()) {
+            self.explore.trim(limit);
+        }
+        if let Some(limit) = limit_for(Page::Local, self.local.status_ids().count()) {
+            self.local.trim(limit);
+        }
+        if let Some(limit) = limit_for(Page::Federated, self.federated.status_ids().count()) {
+            self.federated.trim(limit);
+        }
+        if let Some(hashtag) = &mut self.hashtag {
+            if let Some(limit) = limit_for(Page::Hashtags, hashtag.status_ids().count()) {
+                hashtag.trim(limit);
+            }
+        }
     }
+
+    /// Drops the entries and images that no page references anymore.
+    fn retain_references(&mut self) {
+        let mut references = References::default();
+        references.statuses.extend(
+            self.home
+                .status_ids()
+                .chain(self.explore.status_ids())
+                .chain(self.local.status_ids())
+                .chain(self.federated.status_ids())
+                .chain(self.hashtag.iter().flat_map(|hashtag| hashtag.status_ids()))
+                .chain(self.conversations.status_ids())
+                .map(ToString::to_string),
+        );
+        references.notifications.extend(
+            self.notifications
+                .notification_ids()
+                .map(ToString::to_string),
+        );
+        match &self.context_page {
+            ContextPage::Status(id) => {
+                references.statuses.insert(id.to_string());
+            }
+            ContextPage::Account(account) => {
+                references.images.extend(
+                    utils::extract_account_images(account)
+                        .into_iter()
+                        .map(|(url, _)| url),
+                );
+            }
+            ContextPage::About | ContextPage::Settings => (),
+        }
+        if let Some(Dialog::Reply(new_status)) = self.dialog_pages.front() {
+            references
+                .statuses
+                .extend(new_status.in_reply_to_id.clone());
+        }
+        references
+            .images
+            .extend(self.visible_images().into_iter().map(|(url, _)| url));
+        if let Some(viewer) = &self.viewer {
+            references
+                .images
+                .extend(viewer.images().into_iter().map(|(url, _)| url));
+        }
+
+        self.cache.retain(&references);
+    }
+
+    /// The statuses listed on the active page, from top to bottom.
+    fn page_status_ids(&self) -> Vec<&StatusId> {
+        match self.nav.active_data::<Page>() {
+            Some(Page::Home) => self.home.status_ids().collect(),
+            Some(Page::Conversations) => self.conversations.status_ids().collect(),
+            Some(Page::Explore) => self.explore.status_ids().collect(),
+            Some(Page::Local) => self.local.status_ids().collect(),
+            Some(Page::Federated) => self.federated.status_ids().collect(),
+            Some(Page::Hashtags) => self
+                .hashtag
+                .iter()
+                .flat_map(|hashtag| hashtag.status_ids())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The status picked with the keyboard, or the boosted status if it's a boost.
+    fn selected_status(&self) -> Option<&Status> {
+        let status = self.cache.statuses.get(self.cache.selected.as_ref()?)?;
+        Some(match &status.reblog {
+            Some(reblog) => self
+                .cache
+                .statuses
+                .get(&reblog.id.to_string())
+                .unwrap_or(reblog),
+            None => status,
+        })
+    }
+
+    /// Whether an animation is shown, which is what the clock ticks for.
+    fn animating(&self) -> bool {
+        if !self.cache.animate || self.cache.animations.is_empty() {
+            return false;
+        }
+        let drawer = match &self.context_page {
+            ContextPage::Account(account) if self.core.window.show_context => {
+                utils::extract_account_images(account)
+            }
+            ContextPage::Status(id) if self.core.window.show_context => self
+                .cache
+                .statuses
+                .get(&id.to_string())
+                .map(utils::extract_status_images)
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+        let viewer = self.viewer.as_ref().map(Viewer::images).unwrap_or_default();
+        self.visible_images()
+            .into_iter()
+            .chain(drawer)
+            .chain(viewer)
+            .any(|(url, thumbnail)| {
+                thumbnail.is_animated() && self.cache.animations.contains_key(&url)
+            })
+    }
+
+    /// Images of the statuses and notifications on the active page.
+    fn visible_images(&self) -> Vec<(Url, images::Thumbnail)> {
+        let statuses = self.page_status_ids();
+        let notifications: Vec<&NotificationId> = match self.nav.active_data::<Page>() {
+            Some(Page::Notifications) => self.notifications.notification_ids().collect(),
+            _ => vec![],
+        };
+        let accounts: Vec<&Account> = match self.nav.active_data::<Page>() {
+            Some(Page::FollowRequests) => self.follow_requests.accounts().collect(),
+            Some(Page::Conversations) => self.conversations.accounts().collect(),
+            Some(Page::Suggestions) => self.suggestions.accounts().collect(),
+            _ => vec![],
+        };
+
+        statuses
+            .into_iter()
+            .filter_map(|id| self.cache.statuses.get(&id.to_string()))
+            .flat_map(utils::extract_status_images)
+            .chain(
+                notifications
+                    .into_iter()
+                    .filter_map(|id| self.cache.notifications.get(&id.to_string()))
+                    .flat_map(utils::extract_notification_images),
+            )
+            .chain(accounts.into_iter().flat_map(utils::extract_account_images))
+            .chain(match self.nav.active_data::<Page>() {
+                Some(Page::Explore) => self.explore.images(),
+                Some(Page::Moderation) => self.moderation.images(),
+                _ => vec![],
+            })
+            .chain(self.visible_animations())
+            .collect()
+    }
+
+    /// Animated previews of the statuses on the active page and in the drawer.
+    fn visible_animations(&self) -> Vec<(Url, images::Thumbnail)> {
+        let notifications: Vec<&NotificationId> = match self.nav.active_data::<Page>() {
+            Some(Page::Notifications) => self.notifications.notification_ids().collect(),
+            _ => vec![],
+        };
+        let drawer = match &self.context_page {
+            ContextPage::Status(id) if self.core.window.show_context => Some(id),
+            _ => None,
+        };
+
+        self.page_status_ids()
+            .into_iter()
+            .chain(drawer)
+            .filter_map(|id| self.cache.statuses.get(&id.to_string()))
+            .chain(
+                notifications
+                    .into_iter()
+                    .filter_map(|id| self.cache.notifications.get(&id.to_string()))
+                    .filter_map(|notification| notification.status.as_ref()),
+            )
+            .flat_map(utils::extract_animated_images)
+            .collect()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let statuses = |ids: Vec<&StatusId>| -> Vec<Status> {
+            ids.into_iter()
+                .filter_map(|id| self.cache.statuses.get(&id.to_string()))
+                .take(storage::TIMELINE_LIMIT)
+                .cloned()
+                .collect()
+        };
+
+        Snapshot {
+            home: statuses(self.home.status_ids().collect()),
+            notifications: self
+                .notifications
+                .notification_ids()
+                .filter_map(|id| self.cache.notifications.get(&id.to_string()))
+                .take(storage::TIMELINE_LIMIT)
+                .cloned()
+                .collect(),
+            explore: statuses(self.explore.status_ids().collect()),
+            local: statuses(self.local.status_ids().collect()),
+            federated: statuses(self.federated.status_ids().collect()),
+            accounts: self
+                .cache
+                .recent_accounts()
+                .take(storage::ACCOUNT_LIMIT)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// The scopes the app registers with, with the admin scopes when moderation was requested.
+fn registration_scopes(moderation: bool) -> Result<Scopes, Error> {
+    let scopes = if moderation {
+        "read write admin:read admin:write"
+    } else {
+        "read write"
+    };
+    Scopes::from_str(scopes).map_err(|err| Error::Login(err.to_string()))
 }
 
 fn instance(instance: impl Into<String>) -> String {
@@ -809,16 +2525,26 @@ where
         instance(self.instance.clone())
     }
 
+    /// The navigation entry of `page`, if it's listed.
+    fn nav_entry(&self, page: &Page) -> Option<nav_bar::Id> {
+        self.nav
+            .iter()
+            .find(|id| self.nav.data::<Page>(*id) == Some(page))
+    }
+
     fn update_navbar(&mut self) {
         self.nav.clear();
 
-        let variants = self
+        let mut variants = self
             .mastodon
             .data
             .token
             .is_empty()
             .then(|| Page::public_variants())
             .unwrap_or_else(|| Page::variants());
+        if self.moderator {
+            variants.push(Page::Moderation);
+        }
 
         for page in variants {
             self.nav
@@ -829,6 +2555,28 @@ where
 
             self.nav.activate_position(0);
         }
+        self.update_unread();
+    }
+
+    /// Shows the number of unread entries next to the pages that track them.
+    fn update_unread(&mut self) {
+        let ids: Vec<nav_bar::Id> = self.nav.iter().collect();
+        for id in ids {
+            let Some(page) = self.nav.data::<Page>(id).cloned() else {
+                continue;
+            };
+            let unread = match page {
+                Page::Home => self.home.unread(),
+                Page::Notifications => self.notifications.unread(),
+                Page::Conversations => self.conversations.unread(),
+                _ => continue,
+            };
+            let text = match unread {
+                0 => page.to_string(),
+                count => fl!("page-unread", page = page.to_string(), count = count),
+            };
+            self.nav.text_set(id, text);
+        }
     }
 }
 
@@ -836,6 +2584,7 @@ where
 pub enum ContextPage {
     #[default]
     About,
+    Settings,
     Account(Account),
     Status(StatusId),
 }
@@ -844,6 +2593,7 @@ impl ContextPage {
     fn title(&self) -> String {
         match self {
             ContextPage::About => fl!("about"),
+            ContextPage::Settings => fl!("settings"),
             ContextPage::Account(_) => fl!("profile"),
             ContextPage::Status(_) => fl!("status"),
         }
@@ -853,6 +2603,7 @@ impl ContextPage {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    Settings,
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -861,6 +2612,19 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::Settings => Message::ToggleContextPage(ContextPage::Settings),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration_scopes_follow_moderation() {
+        let scopes = |moderation| registration_scopes(moderation).unwrap().to_string();
+        assert!(scopes(true).contains("admin:write"));
+        assert!(!scopes(false).contains("admin:"));
+    }
+}
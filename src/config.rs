@@ -4,13 +4,128 @@ use cosmic::{
     cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, Config, CosmicConfigEntry},
     Application,
 };
+use mastodon_async::prelude::notification::Type;
+use serde::{Deserialize, Serialize};
 
-use crate::app::AppModel;
+use crate::{app::AppModel, shortcuts::Action};
 
 #[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
 #[version = 1]
 pub struct TootConfig {
     pub server: String,
+    pub pause_animations: bool,
+    pub desktop_notifications: DesktopNotifications,
+    /// Whether logging in asks for the admin scopes moderators need.
+    pub moderation: bool,
+    pub key_bindings: KeyBindings,
+}
+
+/// The notification types that are raised as desktop notifications.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DesktopNotifications {
+    pub mentions: bool,
+    pub boosts: bool,
+    pub favourites: bool,
+    /// New followers and follow requests.
+    pub follows: bool,
+    pub polls: bool,
+    /// Posts of accounts with notifications turned on.
+    pub statuses: bool,
+    pub edits: bool,
+    /// Sign-ups and reports, only sent to moderators.
+    pub admin: bool,
+}
+
+impl Default for DesktopNotifications {
+    fn default() -> Self {
+        Self {
+            mentions: true,
+            boosts: false,
+            favourites: false,
+            follows: true,
+            polls: false,
+            statuses: false,
+            edits: false,
+            admin: false,
+        }
+    }
+}
+
+impl DesktopNotifications {
+    pub fn enabled(&self, notification_type: &Type) -> bool {
+        match notification_type {
+            Type::Mention => self.mentions,
+            Type::Reblog => self.boosts,
+            Type::Favourite => self.favourites,
+            Type::Follow | Type::FollowRequest => self.follows,
+            Type::Poll => self.polls,
+            Type::Status => self.statuses,
+            Type::Update => self.edits,
+            Type::SignUp | Type::Report => self.admin,
+        }
+    }
+}
+
+/// The keys of every shortcut, as space-separated sequences like `g h`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub next: String,
+    pub previous: String,
+    pub favourite: String,
+    pub boost: String,
+    pub reply: String,
+    pub compose: String,
+    pub open: String,
+    pub home: String,
+    pub notifications: String,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            next: "j".into(),
+            previous: "k".into(),
+            favourite: "f".into(),
+            boost: "b".into(),
+            reply: "r".into(),
+            compose: "n".into(),
+            open: "Enter".into(),
+            home: "g h".into(),
+            notifications: "g n".into(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> &str {
+        match action {
+            Action::Next => &self.next,
+            Action::Previous => &self.previous,
+            Action::Favourite => &self.favourite,
+            Action::Boost => &self.boost,
+            Action::Reply => &self.reply,
+            Action::Compose => &self.compose,
+            Action::Open => &self.open,
+            Action::Home => &self.home,
+            Action::Notifications => &self.notifications,
+        }
+    }
+
+    pub fn set(&mut self, action: Action, keys: String) {
+        let binding = match action {
+            Action::Next => &mut self.next,
+            Action::Previous => &mut self.previous,
+            Action::Favourite => &mut self.favourite,
+            Action::Boost => &mut self.boost,
+            Action::Reply => &mut self.reply,
+            Action::Compose => &mut self.compose,
+            Action::Open => &mut self.open,
+            Action::Home => &mut self.home,
+            Action::Notifications => &mut self.notifications,
+        };
+        *binding = keys;
+    }
 }
 
 impl TootConfig {
@@ -0,0 +1,378 @@
+//! Raises desktop notifications through the freedesktop notification service on the
+//! session bus, and reports the actions picked on them back to the app.
+
+use std::collections::HashMap;
+
+use cosmic::{
+    iced::{stream, Subscription},
+    Application,
+};
+use futures_util::{SinkExt, Stream, StreamExt};
+use mastodon_async::prelude::{notification::Type, Notification};
+use tokio::sync::OnceCell;
+use zbus::{proxy, zvariant::Value, Connection};
+
+use crate::{
+    app::{self, AppModel},
+    error::Error,
+    fl, html, storage, utils, widgets,
+};
+
+/// The action of clicking the notification itself.
+pub const OPEN: &str = "default";
+pub const REPLY: &str = "reply";
+
+/// Characters of the status shown in the body of a notification.
+const SNIPPET_LENGTH: usize = 200;
+
+static CONNECTION: OnceCell<Connection> = OnceCell::const_new();
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+}
+
+async fn proxy() -> Result<NotificationsProxy<'static>, Error> {
+    let connection = CONNECTION.get_or_try_init(Connection::session).await?;
+    Ok(NotificationsProxy::new(connection).await?)
+}
+
+/// Shows `notification` on the desktop, returning the id the notification service gave it.
+pub async fn notify(notification: &Notification) -> Result<u32, Error> {
+    let summary = widgets::notification::summary(notification, 0);
+    let body = match &notification.status {
+        Some(status) => snippet(&status.content),
+        None => format!("@{}", notification.account.acct),
+    };
+
+    let (open, reply) = (fl!("open"), fl!("reply"));
+    let mut actions = vec![OPEN, open.as_str()];
+    if notification.status.is_some() && matches!(notification.notification_type, Type::Mention) {
+        actions.extend([REPLY, reply.as_str()]);
+    }
+
+    let mut hints = HashMap::new();
+    hints.insert("desktop-entry", Value::from(AppModel::APP_ID));
+    // The avatar is read from the image cache, so it has to be downloaded first.
+    let avatar = &notification.account.avatar_static;
+    let avatar = match utils::get(avatar).await {
+        Ok(_) => storage::image_path(avatar),
+        Err(err) => {
+            tracing::warn!("failed to download avatar for notification: {}", err);
+            None
+        }
+    };
+    if let Some(path) = avatar.as_ref().and_then(|path| path.to_str()) {
+        hints.insert("image-path", Value::from(format!("file://{}", path)));
+    }
+
+    let id = proxy()
+        .await?
+        .notify(
+            &fl!("app-title"),
+            0,
+            AppModel::APP_ID,
+            &summary,
+            &body,
+            &actions,
+            hints,
+            -1,
+        )
+        .await?;
+    Ok(id)
+}
+
+/// The text of a status, shortened to fit a notification.
+fn snippet(content: &str) -> String {
+    let text = html::parse(content)
+        .iter()
+        .map(|paragraph| {
+            paragraph
+                .spans
+                .iter()
+                .map(|span| span.text.as_str())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    match text.char_indices().nth(SNIPPET_LENGTH) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+/// Reports the actions picked on any desktop notification, the app ignores the ids it
+/// didn't raise.
+pub fn actions() -> Subscription<app::Message> {
+    Subscription::run_with_id(
+        "desktop-notifications",
+        stream::channel(1, |mut output| async move {
+            match invoked_actions().await {
+                Ok(actions) => {
+                    let mut actions = std::pin::pin!(actions);
+                    while let Some(message) = actions.next().await {
+                        if let Err(err) = output.send(message).await {
+                            tracing::warn!("failed to send notification action: {}", err);
+                        }
+                    }
+                }
+                Err(err) => tracing::error!("failed to listen for notification actions: {}", err),
+            }
+
+            std::future::pending().await
+        }),
+    )
+}
+
+/// The `ActionInvoked` signals of the notification service as app messages.
+async fn invoked_actions() -> Result<impl Stream<Item = app::Message>, Error> {
+    let signals = proxy().await?.receive_action_invoked().await?;
+    Ok(signals.filter_map(|signal| async move {
+        let args = signal.args().ok()?;
+        Some(app::Message::DesktopAction(args.id, args.action_key))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        sync::{Arc, Mutex},
+    };
+
+    use zbus::{interface, zvariant::OwnedValue, SignalContext};
+
+    use super::*;
+
+    const PATH: &str = "/org/freedesktop/Notifications";
+
+    /// A private session bus, shut down when dropped.
+    struct Bus(Child);
+
+    impl Bus {
+        /// Starts the bus, returning its address, or `None` without `dbus-daemon`.
+        fn start() -> Option<(Self, String)> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let stdout = daemon.stdout.take()?;
+            let bus = Bus(daemon);
+            let mut address = String::new();
+            BufReader::new(stdout).read_line(&mut address).ok()?;
+            Some((bus, address.trim().to_string()))
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// A notification as the stub service received it.
+    struct Shown {
+        summary: String,
+        actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+    }
+
+    /// Stands in for the notification service, remembering what it was asked to show.
+    struct Service {
+        shown: Arc<Mutex<Vec<Shown>>>,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl Service {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: String,
+            _replaces_id: u32,
+            _app_icon: String,
+            summary: String,
+            _body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let mut shown = self.shown.lock().unwrap();
+            shown.push(Shown {
+                summary,
+                actions,
+                hints,
+            });
+            shown.len() as u32
+        }
+
+        #[zbus(signal)]
+        async fn action_invoked(
+            context: &SignalContext<'_>,
+            id: u32,
+            action_key: &str,
+        ) -> zbus::Result<()>;
+    }
+
+    fn mention() -> Notification {
+        let account = serde_json::json!({
+            "id": "1",
+            "username": "alice",
+            "acct": "alice@example.com",
+            "display_name": "Alice",
+            "locked": false,
+            "bot": false,
+            "group": false,
+            "discoverable": true,
+            "created_at": "2024-01-01T00:00:00.000Z",
+            "note": "",
+            "url": "https://example.com/@alice",
+            "avatar": "https://example.com/avatar.png",
+            "avatar_static": "https://example.com/avatar.png",
+            "header": "https://example.com/header.png",
+            "header_static": "https://example.com/header.png",
+            "followers_count": 0,
+            "following_count": 0,
+            "statuses_count": 1,
+            "last_status_at": "2024-01-01",
+            "emojis": [],
+            "fields": [],
+        });
+        serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "type": "mention",
+            "created_at": "2024-01-01T00:00:00.000Z",
+            "account": account,
+            "status": {
+                "id": "1",
+                "uri": "https://example.com/statuses/1",
+                "url": "https://example.com/@alice/1",
+                "created_at": "2024-01-01T00:00:00.000Z",
+                "account": account,
+                "content": "<p>Hello</p>",
+                "visibility": "public",
+                "sensitive": false,
+                "spoiler_text": "",
+                "media_attachments": [],
+                "mentions": [],
+                "tags": [],
+                "emojis": [],
+                "reblogs_count": 0,
+                "favourites_count": 0,
+                "replies_count": 0,
+                "in_reply_to_id": null,
+                "in_reply_to_account_id": null,
+                "reblog": null,
+                "poll": null,
+                "card": null,
+                "language": "en",
+                "edited_at": null,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn notifications_reach_the_service_and_report_actions() {
+        let Some((_bus, address)) = Bus::start() else {
+            eprintln!("skipped, dbus-daemon isn't available");
+            return;
+        };
+        let cache = std::env::temp_dir().join(format!("toot-desktop-{}", std::process::id()));
+        // The session bus and the image cache holding the avatar are found through the
+        // environment.
+        std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &address);
+        std::env::set_var("XDG_CACHE_HOME", &cache);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let shown = Arc::new(Mutex::new(Vec::new()));
+            let service = Service {
+                shown: shown.clone(),
+            };
+            let service = zbus::connection::Builder::address(address.as_str())
+                .unwrap()
+                .name("org.freedesktop.Notifications")
+                .unwrap()
+                .serve_at(PATH, service)
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+
+            let notification = mention();
+            let avatar = &notification.account.avatar_static;
+            storage::save_image(avatar, b"avatar").await.unwrap();
+            // Subscribed before the signal is emitted, so it can't be missed.
+            let actions = invoked_actions().await.unwrap();
+
+            let id = notify(&notification).await.unwrap();
+            let mut shown = shown.lock().unwrap().pop().unwrap();
+            assert_eq!(
+                shown.summary,
+                widgets::notification::summary(&notification, 0)
+            );
+            assert_eq!(
+                shown.actions,
+                [
+                    OPEN.to_string(),
+                    fl!("open"),
+                    REPLY.to_string(),
+                    fl!("reply")
+                ]
+            );
+            let image = shown
+                .hints
+                .remove("image-path")
+                .and_then(|value| String::try_from(value).ok());
+            let path = storage::image_path(avatar).unwrap();
+            assert_eq!(
+                image,
+                Some(format!("file://{}", path.display())),
+                "the avatar should be shown from the image cache"
+            );
+
+            let service = service
+                .object_server()
+                .interface::<_, Service>(PATH)
+                .await
+                .unwrap();
+            Service::action_invoked(service.signal_context(), id, REPLY)
+                .await
+                .unwrap();
+            let mut actions = std::pin::pin!(actions);
+            match actions.next().await {
+                Some(app::Message::DesktopAction(invoked, key)) => {
+                    assert_eq!((invoked, key.as_str()), (id, REPLY));
+                }
+                _ => panic!("the action should be reported as a desktop action"),
+            }
+        });
+
+        let _ = std::fs::remove_dir_all(cache);
+    }
+}
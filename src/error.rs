@@ -1,5 +1,8 @@
+use chrono::{DateTime, Local, Utc};
 use thiserror::Error;
 
+use crate::fl;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Mastodon API error: {0}")]
@@ -7,5 +10,86 @@ pub enum Error {
     #[error("Iced error: {0}")]
     Iced(#[from] cosmic::iced::Error),
     #[error("Reqwest error: {0}")]
-    Reqwest(#[from] reqwest::Error),
+    Reqwest(reqwest::Error),
+    #[error("Authentication error: {0}")]
+    Auth(String),
+    #[error("Login error: {0}")]
+    Login(String),
+    #[error("Rate limited until {reset:?}")]
+    RateLimited { reset: Option<DateTime<Utc>> },
+    #[error("Network error: {0}")]
+    Network(reqwest::Error),
+    #[error("Keyring error: {0}")]
+    Keyring(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Image error: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("D-Bus error: {0}")]
+    DBus(#[from] zbus::Error),
+    #[error("Task error: {0}")]
+    Task(#[from] tokio::task::JoinError),
+    #[cfg(feature = "video")]
+    #[error("Playback error: {0}")]
+    Playback(String),
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() || err.is_connect() {
+            Error::Network(err)
+        } else {
+            Error::Reqwest(err)
+        }
+    }
+}
+
+impl Error {
+    /// A failed step of the login, where network failures are kept apart from the
+    /// server refusing the login.
+    pub fn login(err: mastodon_async::Error) -> Self {
+        match err {
+            mastodon_async::Error::Http(err) => err.into(),
+            err => Error::Login(err.to_string()),
+        }
+    }
+
+    /// Whether retrying the same request later may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Reqwest(err) => match err.status() {
+                Some(status) => status.is_server_error(),
+                None => err.is_request(),
+            },
+            Error::Network(_) | Error::RateLimited { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether retrying the same request can never succeed, e.g. for a deleted resource.
+    pub fn is_permanent(&self) -> bool {
+        match self {
+            Error::Reqwest(err) => err.status().is_some_and(|status| status.is_client_error()),
+            Error::Auth(_) => true,
+            _ => false,
+        }
+    }
+
+    /// A short explanation of the error, shown in toasts.
+    pub fn user_message(&self) -> String {
+        match self {
+            Error::Auth(_) => fl!("error-auth"),
+            Error::Login(_) => fl!("error-login"),
+            Error::RateLimited { reset: Some(reset) } => fl!(
+                "error-rate-limited-until",
+                time = reset.with_timezone(&Local).format("%H:%M").to_string()
+            ),
+            Error::RateLimited { reset: None } => fl!("error-rate-limited"),
+            Error::Network(_) => fl!("error-network"),
+            Error::Keyring(_) => fl!("error-keyring"),
+            err => fl!("error-unknown", reason = err.to_string()),
+        }
+    }
 }
@@ -0,0 +1,433 @@
+//! Converts the HTML of statuses and bios into paragraphs of styled text. Mastodon
+//! sanitizes that HTML down to a handful of tags, so a full parser isn't needed.
+
+use reqwest::Url;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Paragraph {
+    pub spans: Vec<Span>,
+    /// Whether the paragraph is part of a block quote.
+    pub quote: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+    pub link: Option<Link>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub strikethrough: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Link {
+    Url(Url),
+    /// A mentioned account, identified by the url of its profile.
+    Mention(Url),
+    /// A hashtag, without the `#`.
+    Hashtag(String),
+}
+
+/// Tags that can't be nested and whose content is shown differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Inline {
+    Plain,
+    /// Parts of long urls Mastodon hides.
+    Invisible,
+    /// The visible end of a shortened url.
+    Ellipsis,
+}
+
+#[derive(Debug, Default)]
+struct Parser {
+    paragraphs: Vec<Paragraph>,
+    current: Paragraph,
+    link: Option<Link>,
+    spans: Vec<Inline>,
+    /// Item numbers of the open lists, `None` for bulleted lists.
+    lists: Vec<Option<u32>>,
+    bold: usize,
+    italic: usize,
+    code: usize,
+    strikethrough: usize,
+    quote: usize,
+    preformatted: usize,
+}
+
+pub fn parse(html: &str) -> Vec<Paragraph> {
+    let mut parser = Parser::default();
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        parser.text(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+        parser.tag(&rest[open + 1..open + close]);
+        rest = &rest[open + close + 1..];
+    }
+    parser.text(rest);
+    parser.finish()
+}
+
+impl Parser {
+    fn text(&mut self, text: &str) {
+        if text.is_empty() || self.spans.contains(&Inline::Invisible) {
+            return;
+        }
+        let mut text = decode_entities(text);
+        if self.preformatted == 0 {
+            text = text.replace('\n', " ");
+        }
+        self.push(text);
+    }
+
+    fn push(&mut self, text: String) {
+        let style = Style {
+            bold: self.bold > 0,
+            italic: self.italic > 0,
+            code: self.code > 0 || self.preformatted > 0,
+            strikethrough: self.strikethrough > 0,
+        };
+        if self.current.spans.is_empty() {
+            self.current.quote = self.quote > 0;
+        }
+        match self.current.spans.last_mut() {
+            Some(last) if last.style == style && last.link == self.link => {
+                last.text.push_str(&text)
+            }
+            _ => self.current.spans.push(Span {
+                text,
+                style,
+                link: self.link.clone(),
+            }),
+        }
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag.trim_end_matches('/')),
+        };
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let name = name.to_ascii_lowercase();
+
+        match (name.as_str(), closing) {
+            ("br", false) => self.push("\n".to_string()),
+            ("p" | "pre" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.break_paragraph();
+                if name == "pre" {
+                    self.preformatted += 1;
+                }
+            }
+            ("p" | "pre" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => {
+                self.break_paragraph();
+                if name == "pre" {
+                    self.preformatted = self.preformatted.saturating_sub(1);
+                }
+            }
+            ("blockquote", false) => {
+                self.break_paragraph();
+                self.quote += 1;
+            }
+            ("blockquote", true) => {
+                self.break_paragraph();
+                self.quote = self.quote.saturating_sub(1);
+            }
+            ("ul" | "ol", false) => {
+                self.break_paragraph();
+                let start = attribute(attributes, "start").and_then(|start| start.parse().ok());
+                self.lists
+                    .push((name == "ol").then_some(start.unwrap_or(1)));
+            }
+            ("ul" | "ol", true) => {
+                self.break_paragraph();
+                self.lists.pop();
+            }
+            ("li", false) => {
+                self.break_paragraph();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}{}. ", indent, *number - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.push(marker);
+            }
+            ("li", true) => self.break_paragraph(),
+            ("a", false) => self.link = link(attributes),
+            ("a", true) => self.link = None,
+            ("span", false) => {
+                let class = attribute(attributes, "class").unwrap_or_default();
+                let classes: Vec<&str> = class.split_whitespace().collect();
+                self.spans.push(if classes.contains(&"invisible") {
+                    Inline::Invisible
+                } else if classes.contains(&"ellipsis") {
+                    Inline::Ellipsis
+                } else {
+                    Inline::Plain
+                });
+            }
+            ("span", true) => {
+                if let Some(Inline::Ellipsis) = self.spans.pop() {
+                    self.push("…".to_string());
+                }
+            }
+            ("strong" | "b", false) => self.bold += 1,
+            ("strong" | "b", true) => self.bold = self.bold.saturating_sub(1),
+            ("em" | "i", false) => self.italic += 1,
+            ("em" | "i", true) => self.italic = self.italic.saturating_sub(1),
+            ("code", false) => self.code += 1,
+            ("code", true) => self.code = self.code.saturating_sub(1),
+            ("del" | "s", false) => self.strikethrough += 1,
+            ("del" | "s", true) => self.strikethrough = self.strikethrough.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    fn break_paragraph(&mut self) {
+        let paragraph = std::mem::take(&mut self.current);
+        if paragraph
+            .spans
+            .iter()
+            .any(|span| !span.text.trim().is_empty())
+        {
+            self.paragraphs.push(paragraph);
+        }
+    }
+
+    fn finish(mut self) -> Vec<Paragraph> {
+        self.break_paragraph();
+        for paragraph in &mut self.paragraphs {
+            if let Some(last) = paragraph.spans.last_mut() {
+                last.text.truncate(last.text.trim_end().len());
+            }
+        }
+        self.paragraphs
+    }
+}
+
+/// The target of an `<a>` tag, which Mastodon marks with the `mention` and `hashtag`
+/// classes when it points to an account or a tag.
+fn link(attributes: &str) -> Option<Link> {
+    let url = Url::parse(&attribute(attributes, "href")?).ok()?;
+    let class = attribute(attributes, "class").unwrap_or_default();
+    let classes: Vec<&str> = class.split_whitespace().collect();
+    if classes.contains(&"hashtag") {
+        let tag = url.path_segments()?.last()?;
+        Some(Link::Hashtag(percent_decode(tag)))
+    } else if classes.contains(&"mention") {
+        Some(Link::Mention(url))
+    } else {
+        Some(Link::Url(url))
+    }
+}
+
+/// The decoded value of the attribute `name`, quoted or not.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes.trim_start();
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..end];
+        rest = rest[end..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let (value, remaining) = match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let value = &value[1..];
+                        let close = value.find(quote).unwrap_or(value.len());
+                        (&value[..close], &value[(close + 1).min(value.len())..])
+                    }
+                    _ => {
+                        let close = value.find(char::is_whitespace).unwrap_or(value.len());
+                        (&value[..close], &value[close..])
+                    }
+                };
+                rest = remaining.trim_start();
+                value
+            }
+            None => "",
+        };
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value));
+        }
+    }
+    None
+}
+
+/// Decodes the `%XX` escapes of a url path segment, such as a hashtag with accents.
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| segment.get(index + 1..index + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(paragraphs: &[Paragraph]) -> Vec<String> {
+        paragraphs
+            .iter()
+            .map(|paragraph| {
+                paragraph
+                    .spans
+                    .iter()
+                    .map(|span| span.text.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_named_and_numeric_entities() {
+        assert_eq!(decode_entities("a &amp; b &lt;c&gt;"), "a & b <c>");
+        assert_eq!(decode_entities("&quot;&apos;&#39;&#x27;"), "\"'''");
+        assert_eq!(decode_entities("&nbsp;"), "\u{a0}");
+    }
+
+    #[test]
+    fn leaves_unknown_entities_alone() {
+        assert_eq!(
+            decode_entities("AT&T; &bogus; & 100%"),
+            "AT&T; &bogus; & 100%"
+        );
+        assert_eq!(decode_entities("&#xffffffff;"), "&#xffffffff;");
+    }
+
+    #[test]
+    fn splits_paragraphs_and_line_breaks() {
+        let paragraphs = parse("<p>one<br>two</p><p>three</p>");
+        assert_eq!(texts(&paragraphs), ["one\ntwo", "three"]);
+    }
+
+    #[test]
+    fn nested_styles_split_spans() {
+        let paragraphs = parse("<p><strong>bold <em>both</em></strong> plain</p>");
+        let spans = &paragraphs[0].spans;
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].text, "bold ");
+        assert!(spans[0].style.bold && !spans[0].style.italic);
+        assert_eq!(spans[1].text, "both");
+        assert!(spans[1].style.bold && spans[1].style.italic);
+        assert_eq!(spans[2].text, " plain");
+        assert_eq!(spans[2].style, Style::default());
+    }
+
+    #[test]
+    fn hides_invisible_parts_of_urls() {
+        let html = r#"<p><a href="https://example.com/a/very/long/path"><span class="invisible">https://</span><span class="ellipsis">example.com/a/very</span><span class="invisible">/long/path</span></a></p>"#;
+        let paragraphs = parse(html);
+        assert_eq!(texts(&paragraphs), ["example.com/a/very…"]);
+        let url = Url::parse("https://example.com/a/very/long/path").unwrap();
+        assert_eq!(paragraphs[0].spans[0].link, Some(Link::Url(url)));
+    }
+
+    #[test]
+    fn recognizes_mentions() {
+        let html = r#"<p><span class="h-card"><a href="https://example.com/@alice" class="u-url mention">@<span>alice</span></a></span> hi</p>"#;
+        let paragraphs = parse(html);
+        assert_eq!(texts(&paragraphs), ["@alice hi"]);
+        let url = Url::parse("https://example.com/@alice").unwrap();
+        assert_eq!(paragraphs[0].spans[0].link, Some(Link::Mention(url)));
+    }
+
+    #[test]
+    fn recognizes_hashtags_with_escaped_names() {
+        let html = r#"<p><a href="https://example.com/tags/caf%C3%A9" class="mention hashtag" rel="tag">#<span>café</span></a></p>"#;
+        let paragraphs = parse(html);
+        assert_eq!(texts(&paragraphs), ["#café"]);
+        let hashtag = Link::Hashtag("café".to_string());
+        assert_eq!(paragraphs[0].spans[0].link, Some(hashtag));
+    }
+
+    #[test]
+    fn percent_decoding_keeps_invalid_escapes() {
+        assert_eq!(percent_decode("rust%20lang"), "rust lang");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn numbers_ordered_lists() {
+        let paragraphs = parse(r#"<ol start="3"><li>a</li><li>b</li></ol>"#);
+        assert_eq!(texts(&paragraphs), ["3. a", "4. b"]);
+    }
+
+    #[test]
+    fn marks_quotes() {
+        let paragraphs = parse("<blockquote><p>quoted</p></blockquote><p>reply</p>");
+        assert!(paragraphs[0].quote);
+        assert!(!paragraphs[1].quote);
+    }
+}
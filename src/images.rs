@@ -0,0 +1,249 @@
+//! Downloads images with a bounded number of parallel requests, so timelines full of
+//! avatars don't flood the network.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    io::Cursor,
+    time::Duration,
+};
+
+use cosmic::widget::image::Handle;
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    imageops::FilterType,
+    AnimationDecoder, DynamicImage, ImageFormat,
+};
+use reqwest::Url;
+
+use crate::{error::Error, utils};
+
+/// Downloads that may run at the same time.
+const MAX_CONCURRENT: usize = 6;
+/// Attempts made for an image before giving up on it until it's requested again.
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled on every following one.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Bytes the decoded frames of an animation may take, later frames are cut off.
+const MAX_ANIMATION_BYTES: usize = 32 * 1024 * 1024;
+/// Shortest frame delay, faster frames are slowed down to it.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/// The size an image is decoded at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Thumbnail {
+    /// Square avatars, 50px wide.
+    Avatar,
+    /// Avatars that may be animated, decoded frame by frame.
+    AnimatedAvatar,
+    /// The avatar in the profile drawer, 100px wide.
+    ProfileAvatar,
+    /// Custom emoji, 32px high.
+    Emoji,
+    /// Custom emoji that may be animated, decoded frame by frame.
+    AnimatedEmoji,
+    /// Profile headers, 120px high.
+    Header,
+    /// Attachment previews and link cards, fitting in the timeline.
+    Preview,
+    /// GIF attachments, decoded frame by frame at the size of a preview.
+    AnimatedPreview,
+    /// The full image, only used by the media viewer.
+    Original,
+}
+
+impl Thumbnail {
+    fn resize(self, image: DynamicImage) -> DynamicImage {
+        match self {
+            Thumbnail::Avatar | Thumbnail::AnimatedAvatar => {
+                image.resize_to_fill(50, 50, FilterType::Triangle)
+            }
+            Thumbnail::ProfileAvatar => image.resize_to_fill(100, 100, FilterType::Triangle),
+            Thumbnail::Emoji | Thumbnail::AnimatedEmoji if image.height() > 32 => {
+                image.resize(u32::MAX, 32, FilterType::Triangle)
+            }
+            Thumbnail::Header if image.height() > 120 => {
+                image.resize(u32::MAX, 120, FilterType::Triangle)
+            }
+            Thumbnail::Preview | Thumbnail::AnimatedPreview
+                if image.width() > 700 || image.height() > 400 =>
+            {
+                image.resize(700, 400, FilterType::Triangle)
+            }
+            _ => image,
+        }
+    }
+
+    pub fn is_animated(self) -> bool {
+        matches!(
+            self,
+            Thumbnail::AnimatedAvatar | Thumbnail::AnimatedEmoji | Thumbnail::AnimatedPreview
+        )
+    }
+}
+
+/// A decoded image.
+#[derive(Debug, Clone)]
+pub enum Image {
+    Still(Handle),
+    Animated(Animation),
+}
+
+/// The frames of an animated image and how long each of them is shown.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    frames: Vec<(Handle, Duration)>,
+    duration: Duration,
+}
+
+impl Animation {
+    /// The frame shown `elapsed` after the animation started, looping forever.
+    pub fn frame(&self, elapsed: Duration) -> &Handle {
+        let mut position =
+            Duration::from_nanos((elapsed.as_nanos() % self.duration.as_nanos().max(1)) as u64);
+        for (handle, delay) in &self.frames {
+            if position < *delay {
+                return handle;
+            }
+            position -= *delay;
+        }
+        &self.frames[0].0
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = &Handle> {
+        self.frames.iter().map(|(handle, _)| handle)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Loader {
+    queue: VecDeque<(Url, Thumbnail)>,
+    in_flight: HashSet<Url>,
+    /// Urls that can't be loaded, such as deleted media.
+    failed: HashSet<Url>,
+}
+
+impl Loader {
+    /// Queues the urls that aren't already queued, loading or known to be dead.
+    pub fn enqueue(&mut self, urls: impl IntoIterator<Item = (Url, Thumbnail)>) {
+        for (url, thumbnail) in urls {
+            if !self.failed.contains(&url)
+                && !self.in_flight.contains(&url)
+                && !self.queue.iter().any(|(queued, _)| *queued == url)
+            {
+                self.queue.push_back((url, thumbnail));
+            }
+        }
+    }
+
+    /// Takes queued urls while there are free download slots.
+    pub fn start(&mut self) -> Vec<(Url, Thumbnail)> {
+        let mut started = vec![];
+        while self.in_flight.len() < MAX_CONCURRENT {
+            let Some((url, thumbnail)) = self.queue.pop_front() else {
+                break;
+            };
+            self.in_flight.insert(url.clone());
+            started.push((url, thumbnail));
+        }
+        started
+    }
+
+    /// Frees the download slot of `url`, remembering it if it can never be loaded.
+    pub fn finish(&mut self, url: &Url, permanent_failure: bool) {
+        self.in_flight.remove(url);
+        if permanent_failure {
+            self.failed.insert(url.clone());
+        }
+    }
+}
+
+/// Downloads an image, retrying transient failures with an exponential backoff, and
+/// decodes it at the size of `thumbnail` on a blocking thread.
+pub async fn fetch(url: &Url, thumbnail: Thumbnail) -> Result<Image, Error> {
+    let mut attempt = 1;
+    let bytes = loop {
+        match utils::get(url).await {
+            Err(err) if err.is_transient() && attempt < MAX_ATTEMPTS => {
+                tracing::debug!("retrying {}: {}", url, err);
+                tokio::time::sleep(RETRY_DELAY * 2u32.pow(attempt - 1)).await;
+                attempt += 1;
+            }
+            result => break result?,
+        }
+    };
+
+    if thumbnail == Thumbnail::Original {
+        return Ok(Image::Still(Handle::from_bytes(bytes)));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        if thumbnail.is_animated() {
+            decode_animation(&bytes, thumbnail)
+        } else {
+            decode(&bytes, thumbnail).map(Image::Still)
+        }
+    })
+    .await?
+}
+
+fn decode(bytes: &[u8], thumbnail: Thumbnail) -> Result<Handle, Error> {
+    let image = thumbnail
+        .resize(image::load_from_memory(bytes)?)
+        .into_rgba8();
+    Ok(Handle::from_rgba(
+        image.width(),
+        image.height(),
+        image.into_raw(),
+    ))
+}
+
+/// Decodes the frames of a GIF, APNG or WebP, falling back to a still image for
+/// other formats and single frame images.
+fn decode_animation(bytes: &[u8], thumbnail: Thumbnail) -> Result<Image, Error> {
+    let cursor = Cursor::new(bytes);
+    let decoded = match image::guess_format(bytes)? {
+        ImageFormat::Gif => GifDecoder::new(cursor)?.into_frames(),
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(cursor)?;
+            if !decoder.has_animation() {
+                return decode(bytes, thumbnail).map(Image::Still);
+            }
+            decoder.into_frames()
+        }
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(cursor)?;
+            if !decoder.is_apng() {
+                return decode(bytes, thumbnail).map(Image::Still);
+            }
+            decoder.apng().into_frames()
+        }
+        _ => return decode(bytes, thumbnail).map(Image::Still),
+    };
+
+    let mut frames = Vec::new();
+    let mut size = 0;
+    for frame in decoded {
+        let frame = frame?;
+        let delay = Duration::from(frame.delay()).max(MIN_FRAME_DELAY);
+        let image = thumbnail
+            .resize(DynamicImage::ImageRgba8(frame.into_buffer()))
+            .into_rgba8();
+        size += image.as_raw().len();
+        if size > MAX_ANIMATION_BYTES && !frames.is_empty() {
+            tracing::debug!("cutting off an animation after {} frames", frames.len());
+            break;
+        }
+        let handle = Handle::from_rgba(image.width(), image.height(), image.into_raw());
+        frames.push((handle, delay));
+    }
+
+    if frames.len() < 2 {
+        return match frames.pop() {
+            Some((handle, _)) => Ok(Image::Still(handle)),
+            None => decode(bytes, thumbnail).map(Image::Still),
+        };
+    }
+
+    let duration = frames.iter().map(|(_, delay)| *delay).sum();
+    Ok(Image::Animated(Animation { frames, duration }))
+}
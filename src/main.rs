@@ -2,14 +2,23 @@
 
 use error::Error;
 
+mod api;
 mod app;
 mod config;
+mod desktop;
 mod error;
+mod html;
 mod i18n;
+mod images;
+mod outbox;
 mod pages;
+mod scheduler;
 mod settings;
+mod shortcuts;
+mod storage;
 mod subscriptions;
 mod utils;
+mod viewer;
 mod widgets;
 
 fn main() -> Result<(), Error> {
@@ -0,0 +1,278 @@
+//! Write actions waiting to reach the server. Actions are sent in the order they were
+//! taken, and stay queued on disk while the server can't be reached.
+
+use std::collections::VecDeque;
+
+use mastodon_async::{
+    prelude::{Status, StatusId},
+    Mastodon, NewStatus,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{api, error::Error};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// Favourites the status, or removes the favourite when `false`.
+    Favourite(StatusId, bool),
+    /// Boosts the status, or undoes the boost when `false`.
+    Boost(StatusId, bool),
+    /// Bookmarks the status, or removes the bookmark when `false`.
+    Bookmark(StatusId, bool),
+    /// A new status, kept as the JSON body of the request with the idempotency key it
+    /// was queued with, so retries don't post it twice.
+    Post(String, serde_json::Value),
+}
+
+impl Action {
+    pub fn post(status: &NewStatus, poll: Option<&api::PollSource>) -> Result<Self, Error> {
+        let key = uuid::Uuid::new_v4().to_string();
+        Ok(Action::Post(key, api::status_body(status, poll)?))
+    }
+
+    /// The status the action changes or replies to.
+    pub fn status_id(&self) -> Option<String> {
+        match self {
+            Action::Favourite(id, _) | Action::Boost(id, _) | Action::Bookmark(id, _) => {
+                Some(id.to_string())
+            }
+            Action::Post(_, body) => body
+                .get("in_reply_to_id")
+                .and_then(|id| id.as_str())
+                .map(str::to_string),
+        }
+    }
+
+    /// The action that undoes this one, used to roll back a refused action.
+    pub fn undo(&self) -> Option<Action> {
+        match self {
+            Action::Favourite(id, set) => Some(Action::Favourite(id.clone(), !set)),
+            Action::Boost(id, set) => Some(Action::Boost(id.clone(), !set)),
+            Action::Bookmark(id, set) => Some(Action::Bookmark(id.clone(), !set)),
+            Action::Post(..) => None,
+        }
+    }
+
+    /// Shows the action on `status` as if the server already took it.
+    pub fn apply(&self, status: &mut Status) {
+        let (flag, count, set) = match self {
+            Action::Favourite(_, set) => (
+                &mut status.favourited,
+                Some(&mut status.favourites_count),
+                *set,
+            ),
+            Action::Boost(_, set) => (&mut status.reblogged, Some(&mut status.reblogs_count), *set),
+            Action::Bookmark(_, set) => (&mut status.bookmarked, None, *set),
+            Action::Post(..) => return,
+        };
+        if *flag == Some(set) {
+            return;
+        }
+        *flag = Some(set);
+        if let Some(count) = count {
+            *count = if set {
+                count.saturating_add(1)
+            } else {
+                count.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Whether `other` undoes this action, so neither has to be sent.
+    fn cancels(&self, other: &Action) -> bool {
+        match (self, other) {
+            (Action::Favourite(a, set_a), Action::Favourite(b, set_b))
+            | (Action::Boost(a, set_a), Action::Boost(b, set_b))
+            | (Action::Bookmark(a, set_a), Action::Bookmark(b, set_b)) => a == b && set_a != set_b,
+            _ => false,
+        }
+    }
+
+    pub async fn send(&self, mastodon: &Mastodon) -> Result<Status, Error> {
+        match self {
+            Action::Favourite(id, true) => api::favourite(mastodon, id).await,
+            Action::Favourite(id, false) => api::unfavourite(mastodon, id).await,
+            Action::Boost(id, true) => api::reblog(mastodon, id).await,
+            Action::Boost(id, false) => api::unreblog(mastodon, id).await,
+            Action::Bookmark(id, true) => api::bookmark(mastodon, id).await,
+            Action::Bookmark(id, false) => api::unbookmark(mastodon, id).await,
+            Action::Post(key, body) => api::post_status(mastodon, body, key).await,
+        }
+    }
+}
+
+/// Why an action didn't reach the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The server can't be reached, the action waits for the connection to return.
+    Offline,
+    /// The server can't take the action right now, it's sent again later.
+    Later,
+    /// The server refused the action, sending it again won't help.
+    Rejected,
+}
+
+impl From<&Error> for Failure {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::Network(_) => Failure::Offline,
+            err if err.is_transient() => Failure::Later,
+            _ => Failure::Rejected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    actions: VecDeque<Action>,
+}
+
+impl Outbox {
+    /// Queues `action`, dropping a queued action it undoes instead. The action being
+    /// sent is left alone, as it may already have reached the server.
+    pub fn push(&mut self, action: Action, sending: bool) {
+        let cancelled = self
+            .actions
+            .iter()
+            .skip(usize::from(sending))
+            .rposition(|queued| queued.cancels(&action));
+        match cancelled {
+            Some(index) => {
+                self.actions.remove(index + usize::from(sending));
+            }
+            None => self.actions.push_back(action),
+        }
+    }
+
+    /// Adds the actions of `other` after the queued ones.
+    pub fn append(&mut self, other: Outbox) {
+        for action in other.actions {
+            self.push(action, false);
+        }
+    }
+
+    pub fn front(&self) -> Option<&Action> {
+        self.actions.front()
+    }
+
+    /// Removes `action` once it was sent or given up on.
+    pub fn remove(&mut self, action: &Action) {
+        if let Some(index) = self.actions.iter().position(|queued| queued == action) {
+            self.actions.remove(index);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Action> {
+        self.actions.iter()
+    }
+
+    /// The statuses with queued actions.
+    pub fn pending(&self) -> impl Iterator<Item = String> + '_ {
+        self.actions.iter().filter_map(Action::status_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(id: &str) -> StatusId {
+        StatusId::new(id)
+    }
+
+    fn outbox(actions: &[Action]) -> Outbox {
+        let mut outbox = Outbox::default();
+        for action in actions {
+            outbox.push(action.clone(), false);
+        }
+        outbox
+    }
+
+    #[test]
+    fn unfavourite_cancels_a_queued_favourite() {
+        let outbox = outbox(&[
+            Action::Favourite(id("1"), true),
+            Action::Favourite(id("1"), false),
+        ]);
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn only_the_same_status_and_kind_cancel() {
+        let outbox = outbox(&[
+            Action::Favourite(id("1"), true),
+            Action::Favourite(id("2"), false),
+            Action::Boost(id("1"), false),
+            Action::Favourite(id("1"), true),
+        ]);
+        assert_eq!(outbox.len(), 4);
+    }
+
+    #[test]
+    fn toggling_back_and_forth_leaves_other_actions_queued() {
+        let outbox = outbox(&[
+            Action::Favourite(id("1"), true),
+            Action::Bookmark(id("1"), true),
+            Action::Favourite(id("1"), false),
+            Action::Favourite(id("1"), true),
+            Action::Favourite(id("1"), false),
+        ]);
+        let actions: Vec<&Action> = outbox.iter().collect();
+        assert_eq!(actions, [&Action::Bookmark(id("1"), true)]);
+    }
+
+    #[test]
+    fn the_action_being_sent_is_not_cancelled() {
+        let mut outbox = outbox(&[Action::Favourite(id("1"), true)]);
+        outbox.push(Action::Favourite(id("1"), false), true);
+        let actions: Vec<&Action> = outbox.iter().collect();
+        assert_eq!(
+            actions,
+            [
+                &Action::Favourite(id("1"), true),
+                &Action::Favourite(id("1"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn posts_are_never_cancelled() {
+        let post = Action::Post("1".to_string(), serde_json::json!({ "status": "hello" }));
+        let outbox = outbox(&[post.clone(), post]);
+        assert_eq!(outbox.len(), 2);
+    }
+
+    #[test]
+    fn posts_get_their_own_idempotency_key() {
+        let status = NewStatus {
+            status: Some("hello".to_string()),
+            ..Default::default()
+        };
+        let (Ok(Action::Post(a, _)), Ok(Action::Post(b, _))) =
+            (Action::post(&status, None), Action::post(&status, None))
+        else {
+            panic!("posts should be queued as posts");
+        };
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn undo_flips_toggles() {
+        assert_eq!(
+            Action::Boost(id("1"), true).undo(),
+            Some(Action::Boost(id("1"), false))
+        );
+        assert_eq!(
+            Action::Post(String::new(), serde_json::Value::Null).undo(),
+            None
+        );
+    }
+}
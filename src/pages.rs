@@ -1,28 +1,161 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::LazyLock};
 
-use crate::fl;
+use cosmic::{
+    iced::{Alignment, Length},
+    iced_widget::scrollable::Viewport,
+    widget, Apply, Element,
+};
+use mastodon_async::prelude::Account;
 
+use crate::{
+    api, fl,
+    utils::{self, Cache},
+};
+
+pub mod conversations;
+pub mod explore;
+pub mod follow_requests;
 pub mod home;
+pub mod moderation;
 pub mod notifications;
 pub mod public;
+pub mod scheduled;
+pub mod suggestions;
+
+/// Entries kept by pages that aren't visible, older ones are loaded again when scrolling.
+pub const PAGE_LIMIT: usize = 200;
+
+/// The scrollable of the timeline pages, scrolled along with the keyboard selection.
+pub static SCROLLABLE: LazyLock<widget::Id> = LazyLock::new(widget::Id::unique);
 
 pub trait MastodonPage {
     fn is_authenticated(&self) -> bool;
 }
 
+/// Shown below a paginated list while the next page loads or once there are no more pages.
+pub fn footer<'a, M: 'a>(loading: bool, end: bool) -> Option<Element<'a, M>> {
+    let text = if loading {
+        fl!("loading")
+    } else if end {
+        fl!("end-of-timeline")
+    } else {
+        return None;
+    };
+
+    Some(
+        widget::text::caption(text)
+            .apply(widget::container)
+            .center_x(Length::Fill)
+            .into(),
+    )
+}
+
+/// How far down a list is scrolled, where a list that doesn't fill the viewport counts
+/// as scrolled to the end so the next page is loaded.
+pub fn scrolled(viewport: Viewport) -> f32 {
+    if viewport.content_bounds().height <= viewport.bounds().height {
+        1.0
+    } else {
+        viewport.relative_offset().y
+    }
+}
+
+/// The row above a list, with a refresh button that `refresh` enables, and a caption
+/// while the list is `loading`.
+pub fn refresh_header<'a, M: Clone + 'a>(loading: bool, refresh: Option<M>) -> Element<'a, M> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    widget::row()
+        .push(widget::horizontal_space())
+        .push_maybe(loading.then(|| widget::text::caption(fl!("loading"))))
+        .push(
+            widget::button::icon(widget::icon::from_name("view-refresh-symbolic"))
+                .tooltip(fl!("refresh"))
+                .on_press_maybe(refresh),
+        )
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_xs)
+        .into()
+}
+
+/// Shown in place of a list that has nothing in it.
+pub fn empty<'a, M: 'a>(text: String) -> Element<'a, M> {
+    widget::text(text)
+        .apply(widget::container)
+        .center_x(Length::Fill)
+        .into()
+}
+
+/// The avatar, name and handle of `account`, each opening it with `open`. Actions on
+/// the account are pushed after it.
+pub fn profile<'a, M: Clone + 'a>(
+    account: &'a Account,
+    cache: &'a Cache,
+    open: M,
+) -> widget::Row<'a, M> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    widget::row()
+        .push(
+            widget::button::image(
+                cache
+                    .avatar(account)
+                    .cloned()
+                    .unwrap_or(utils::fallback_handle()),
+            )
+            .width(40)
+            .height(40)
+            .on_press(open.clone()),
+        )
+        .push(
+            widget::column()
+                .push(crate::widgets::inline::text(
+                    &account.display_name,
+                    &account.emojis,
+                    cache,
+                    14,
+                ))
+                .push(widget::button::link(format!("@{}", account.acct)).on_press(open)),
+        )
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_xs)
+}
+
+/// Separates the entries that came in since the timeline was last read from the rest.
+pub fn last_read_divider<'a, M: 'a>() -> Element<'a, M> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    widget::row()
+        .push(widget::divider::horizontal::default())
+        .push(widget::text::caption(fl!("last-read")))
+        .push(widget::divider::horizontal::default())
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_xs)
+        .padding([0, spacing.space_xs])
+        .into()
+}
+
+/// Whether an entry with `id` came in after `last_read`.
+pub fn is_unread(id: &str, last_read: Option<&str>) -> bool {
+    last_read.is_some_and(|last_read| api::compare_ids(id, last_read).is_gt())
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 pub enum Page {
     #[default]
     Home,
     Notifications,
+    FollowRequests,
+    Conversations,
+    Scheduled,
     Search,
     Favorites,
     Bookmarks,
     Hashtags,
     Lists,
+    Suggestions,
     Explore,
     Local,
     Federated,
+    /// Only listed when the access token has admin scopes.
+    Moderation,
 }
 
 impl Display for Page {
@@ -30,14 +163,19 @@ impl Display for Page {
         match self {
             Page::Home => write!(f, "{}", fl!("home")),
             Page::Notifications => write!(f, "{}", fl!("notifications")),
+            Page::FollowRequests => write!(f, "{}", fl!("follow-requests")),
+            Page::Conversations => write!(f, "{}", fl!("conversations")),
+            Page::Scheduled => write!(f, "{}", fl!("scheduled")),
             Page::Search => write!(f, "{}", fl!("search")),
             Page::Favorites => write!(f, "{}", fl!("favorites")),
             Page::Bookmarks => write!(f, "{}", fl!("bookmarks")),
             Page::Hashtags => write!(f, "{}", fl!("hashtags")),
             Page::Lists => write!(f, "{}", fl!("lists")),
+            Page::Suggestions => write!(f, "{}", fl!("who-to-follow")),
             Page::Explore => write!(f, "{}", fl!("explore")),
             Page::Local => write!(f, "{}", fl!("local")),
             Page::Federated => write!(f, "{}", fl!("federated")),
+            Page::Moderation => write!(f, "{}", fl!("moderation")),
         }
     }
 }
@@ -57,11 +195,15 @@ impl Page {
         vec![
             Self::Home,
             Self::Notifications,
+            Self::FollowRequests,
+            Self::Conversations,
+            Self::Scheduled,
             Self::Search,
             Self::Favorites,
             Self::Bookmarks,
             Self::Hashtags,
             Self::Lists,
+            Self::Suggestions,
             Self::Explore,
             Self::Local,
             Self::Federated,
@@ -72,14 +214,19 @@ impl Page {
         match self {
             Page::Home => "user-home-symbolic",
             Page::Notifications => "emblem-important-symbolic",
+            Page::FollowRequests => "contact-new-symbolic",
+            Page::Conversations => "mail-send-receive-symbolic",
+            Page::Scheduled => "document-open-recent-symbolic",
             Page::Search => "folder-saved-search-symbolic",
             Page::Favorites => "starred-symbolic",
             Page::Bookmarks => "bookmark-new-symbolic",
             Page::Hashtags => "lang-include-symbolic",
             Page::Lists => "view-list-symbolic",
+            Page::Suggestions => "system-users-symbolic",
             Page::Explore => "find-location-symbolic",
             Page::Local => "network-server-symbolic",
             Page::Federated => "network-workgroup-symbolic",
+            Page::Moderation => "security-high-symbolic",
         }
     }
 }
@@ -0,0 +1,352 @@
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length, Subscription},
+    iced_widget::scrollable::{Direction, Scrollbar},
+    widget, Apply, Element,
+};
+use mastodon_async::{
+    prelude::{Account, Mastodon, StatusId, Visibility},
+    NewStatus,
+};
+
+use crate::{
+    api::{self, Conversation, Pagination},
+    app, fl,
+    utils::{self, Cache},
+    widgets::{self, status::StatusOptions},
+};
+
+use super::MastodonPage;
+
+/// Avatars shown for the participants of a conversation, the rest are only named.
+const PARTICIPANT_AVATARS: usize = 4;
+
+/// Threads of direct messages, with the most recently active first.
+#[derive(Debug, Clone)]
+pub struct Conversations {
+    pub mastodon: Mastodon,
+    conversations: Vec<Conversation>,
+    loading: Option<Pagination>,
+    end: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SetClient(Mastodon),
+    Refresh,
+    Scrolled(f32),
+    Load(Pagination),
+    Loaded(Pagination, Vec<Conversation>),
+    LoadFailed(String),
+    MarkRead(String),
+    /// Opens the composer to reply to the last status of a conversation.
+    Reply(String),
+    /// Drops the conversations a deleted status was the last of, they come back with
+    /// their earlier statuses when refreshed.
+    DeleteStatus(String),
+    Status(widgets::status::Message),
+}
+
+impl MastodonPage for Conversations {
+    fn is_authenticated(&self) -> bool {
+        !self.mastodon.data.token.is_empty()
+    }
+}
+
+impl Conversations {
+    pub fn new(mastodon: Mastodon) -> Self {
+        Self {
+            mastodon,
+            conversations: vec![],
+            loading: Some(Pagination::Latest),
+            end: false,
+        }
+    }
+
+    pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let refresh = super::refresh_header(
+            matches!(self.loading, Some(Pagination::Latest)),
+            self.loading.is_none().then_some(Message::Refresh),
+        );
+
+        let conversations: Element<_> = if self.conversations.is_empty() && self.loading.is_none() {
+            super::empty(fl!("no-conversations"))
+        } else {
+            widget::settings::section()
+                .extend(
+                    self.conversations
+                        .iter()
+                        .map(|conversation| thread(conversation, cache)),
+                )
+                .into()
+        };
+
+        widget::column()
+            .push(refresh)
+            .push(conversations)
+            .push_maybe(super::footer(
+                matches!(self.loading, Some(Pagination::Older { .. })),
+                self.end && !self.conversations.is_empty(),
+            ))
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .id(super::SCROLLABLE.clone())
+            .direction(Direction::Vertical(
+                Scrollbar::default().spacing(spacing.space_xxs),
+            ))
+            .on_scroll(|viewport| Message::Scrolled(super::scrolled(viewport)))
+            .apply(widget::container)
+            .max_width(700)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let unread = self.unread();
+        let mut tasks = vec![];
+        match message {
+            Message::SetClient(mastodon) => {
+                self.mastodon = mastodon;
+                if self.loading.is_none() {
+                    self.loading = Some(Pagination::Latest);
+                }
+            }
+            Message::Refresh => {
+                if self.loading.is_none() {
+                    self.loading = Some(Pagination::Latest);
+                }
+            }
+            Message::Scrolled(offset) => {
+                if self.loading.is_none() && !self.end && offset >= 1.0 {
+                    self.loading = self.status_ids().last().map(|max_id| Pagination::Older {
+                        max_id: max_id.to_string(),
+                    });
+                }
+            }
+            Message::Loaded(pagination, conversations) => {
+                self.loading = None;
+                for conversation in &conversations {
+                    let accounts = conversation.accounts.iter();
+                    tasks.push(cosmic::task::message(app::Message::Fetch(
+                        accounts.flat_map(utils::extract_account_images).collect(),
+                    )));
+                    if let Some(status) = &conversation.last_status {
+                        tasks.push(cosmic::task::message(app::Message::Fetch(
+                            utils::extract_status_images(status),
+                        )));
+                        tasks.push(cosmic::task::message(app::Message::CacheStatus(
+                            status.clone(),
+                        )));
+                    }
+                }
+                self.merge(pagination, conversations);
+            }
+            Message::Load(pagination) => {
+                if self.loading.is_none() {
+                    self.loading = Some(pagination);
+                }
+            }
+            Message::LoadFailed(message) => {
+                let retry = self.loading.take().map(|pagination| {
+                    Box::new(app::Message::Conversations(Message::Load(pagination)))
+                });
+                tasks.push(cosmic::task::message(app::Message::Error(message, retry)));
+            }
+            Message::MarkRead(id) => {
+                let Some(conversation) = self.conversations.iter_mut().find(|c| c.id == id) else {
+                    return Task::none();
+                };
+                conversation.unread = false;
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::read_conversation(&mastodon, &id).await {
+                        Ok(()) => app::Message::None,
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Conversations(Message::MarkRead(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::Reply(id) => {
+                let Some(conversation) = self.conversations.iter().find(|c| c.id == id) else {
+                    return Task::none();
+                };
+                let Some(status) = &conversation.last_status else {
+                    return Task::none();
+                };
+                let mentions: Vec<String> = conversation
+                    .accounts
+                    .iter()
+                    .map(|account| format!("@{}", account.acct))
+                    .collect();
+                let new_status = NewStatus {
+                    in_reply_to_id: Some(status.id.to_string()),
+                    status: Some(format!("{} ", mentions.join(" "))),
+                    visibility: Some(Visibility::Direct),
+                    ..Default::default()
+                };
+                tasks.push(cosmic::task::message(app::Message::Dialog(
+                    app::DialogAction::Open(app::Dialog::Reply(new_status)),
+                )));
+                if conversation.unread {
+                    tasks.push(self.update(Message::MarkRead(id)));
+                }
+            }
+            Message::DeleteStatus(id) => self.conversations.retain(|conversation| {
+                !conversation
+                    .last_status
+                    .as_ref()
+                    .is_some_and(|status| status.id.to_string() == id)
+            }),
+            Message::Status(message) => tasks.push(widgets::status::update(message)),
+        }
+        if self.unread() != unread {
+            tasks.push(cosmic::task::message(app::Message::UnreadChanged));
+        }
+        Task::batch(tasks)
+    }
+
+    /// Conversations with statuses the user hasn't read.
+    pub fn unread(&self) -> usize {
+        self.conversations
+            .iter()
+            .filter(|conversation| conversation.unread)
+            .count()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.loading {
+            Some(pagination) if self.is_authenticated() => {
+                crate::subscriptions::conversations::conversations(
+                    self.mastodon.clone(),
+                    pagination.clone(),
+                )
+            }
+            _ => Subscription::none(),
+        }
+    }
+
+    /// The last statuses of the loaded conversations, newest first.
+    pub fn status_ids(&self) -> impl Iterator<Item = &StatusId> {
+        self.conversations
+            .iter()
+            .filter_map(|conversation| conversation.last_status.as_ref())
+            .map(|status| &status.id)
+    }
+
+    /// The participants of the loaded conversations.
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.conversations
+            .iter()
+            .flat_map(|conversation| conversation.accounts.iter())
+    }
+
+    /// Drops the oldest conversations past `limit`, which are loaded again when scrolling down.
+    pub fn trim(&mut self, limit: usize) {
+        if self.loading.is_none() && self.conversations.len() > limit {
+            self.conversations.truncate(limit);
+            self.end = false;
+        }
+    }
+
+    /// Adds a fetched page, replacing conversations that were already loaded and
+    /// keeping the most recently active first.
+    fn merge(&mut self, pagination: Pagination, conversations: Vec<Conversation>) {
+        if matches!(pagination, Pagination::Older { .. }) || self.conversations.is_empty() {
+            self.end = conversations.len() < api::PAGE_SIZE;
+        }
+        for conversation in conversations {
+            self.conversations
+                .retain(|loaded| loaded.id != conversation.id);
+            self.conversations.push(conversation);
+        }
+        let last_status = |conversation: &Conversation| {
+            conversation
+                .last_status
+                .as_ref()
+                .map(|status| status.id.to_string())
+                .unwrap_or_default()
+        };
+        self.conversations
+            .sort_by(|a, b| api::compare_ids(&last_status(b), &last_status(a)));
+    }
+}
+
+fn thread<'a>(conversation: &'a Conversation, cache: &'a Cache) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let id = &conversation.id;
+
+    let avatars = conversation
+        .accounts
+        .iter()
+        .take(PARTICIPANT_AVATARS)
+        .map(|account| {
+            widget::button::custom(
+                cache
+                    .avatar(account)
+                    .map(|handle| widget::image(handle).width(32))
+                    .unwrap_or(utils::fallback_avatar().width(32)),
+            )
+            .class(cosmic::style::Button::Image)
+            .padding(0)
+            .on_press(Message::Status(widgets::status::Message::OpenAccount(
+                account.clone(),
+            )))
+            .into()
+        });
+
+    let participants = conversation
+        .accounts
+        .iter()
+        .map(|account| account.display_name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let participants = if conversation.unread {
+        widget::text::heading(participants)
+    } else {
+        widget::text::body(participants)
+    };
+
+    let header = widget::row()
+        .extend(avatars)
+        .push(participants)
+        .push(widget::horizontal_space())
+        .push_maybe(conversation.unread.then(|| {
+            widget::button::icon(widget::icon::from_name("mail-read-symbolic"))
+                .tooltip(fl!("mark-as-read"))
+                .on_press(Message::MarkRead(id.clone()))
+        }))
+        .push_maybe(conversation.last_status.is_some().then(|| {
+            widget::button::icon(widget::icon::from_name("mail-reply-sender-symbolic"))
+                .tooltip(fl!("reply"))
+                .on_press(Message::Reply(id.clone()))
+        }))
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_xxs);
+
+    // The cached copy shows favourites and boosts that haven't reached the server yet.
+    let status = conversation.last_status.as_ref().map(|status| {
+        let status = cache.statuses.get(&status.id.to_string()).unwrap_or(status);
+        widget::container(
+            widgets::status(status, StatusOptions::new(true, true, false, true), cache)
+                .map(Message::Status),
+        )
+        .padding(spacing.space_xxs)
+        .class(cosmic::theme::Container::Dialog)
+    });
+
+    let content = widget::column()
+        .push(header)
+        .push_maybe(status)
+        .spacing(spacing.space_xs);
+
+    widget::settings::flex_item_row(vec![content.into()])
+        .padding(spacing.space_xs)
+        .into()
+}
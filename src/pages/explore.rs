@@ -0,0 +1,370 @@
+use std::{collections::VecDeque, mem, str::FromStr};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Background, Length, Subscription},
+    iced_widget::scrollable::{Direction, Scrollbar},
+    widget::{self, segmented_button},
+    Apply, Element,
+};
+use mastodon_async::prelude::{Mastodon, Status, StatusId};
+use reqwest::Url;
+
+use crate::{
+    api::{self, History, TrendingLink, TrendingTag},
+    app, fl, images,
+    utils::{self, Cache},
+    widgets::{self, status::StatusOptions},
+};
+
+/// Height of the tallest bar of a sparkline.
+const SPARKLINE_HEIGHT: f32 = 24.0;
+
+/// Trending posts, hashtags and links of the instance.
+#[derive(Debug, Clone)]
+pub struct Explore {
+    pub mastodon: Mastodon,
+    tabs: segmented_button::SingleSelectModel,
+    statuses: VecDeque<StatusId>,
+    hashtags: Vec<TrendingTag>,
+    links: Vec<TrendingLink>,
+    loading: Option<Load>,
+    /// Trends of the active tab, loaded once those of another tab finished loading.
+    queued: Option<Load>,
+    /// Whether there are no more trending posts.
+    end: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Posts,
+    Hashtags,
+    Links,
+}
+
+/// The trends being loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Load {
+    /// Trending posts, starting this many posts into the list.
+    Posts(usize),
+    Hashtags,
+    Links,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SetClient(Mastodon),
+    SelectTab(segmented_button::Entity),
+    Refresh,
+    Scrolled(f32),
+    PostsLoaded(usize, Vec<Status>),
+    HashtagsLoaded(Vec<TrendingTag>),
+    LinksLoaded(Vec<TrendingLink>),
+    Load(Load),
+    LoadFailed(String),
+    Restore(Vec<Status>),
+    OpenHashtag(String),
+    OpenLink(Url),
+    Status(widgets::status::Message),
+}
+
+impl Explore {
+    pub fn new(mastodon: Mastodon) -> Self {
+        let tabs = segmented_button::Model::builder()
+            .insert(|tab| tab.text(fl!("posts")).data(Tab::Posts).activate())
+            .insert(|tab| tab.text(fl!("hashtags")).data(Tab::Hashtags))
+            .insert(|tab| tab.text(fl!("links")).data(Tab::Links))
+            .build();
+
+        Self {
+            mastodon,
+            tabs,
+            statuses: VecDeque::new(),
+            hashtags: vec![],
+            links: vec![],
+            loading: Some(Load::Posts(0)),
+            queued: None,
+            end: false,
+        }
+    }
+
+    pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let header = widget::row()
+            .push(widget::tab_bar::horizontal(&self.tabs).on_activate(Message::SelectTab))
+            .push(
+                widget::button::icon(widget::icon::from_name("view-refresh-symbolic"))
+                    .tooltip(fl!("refresh"))
+                    .on_press_maybe(self.loading.is_none().then_some(Message::Refresh)),
+            )
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs);
+
+        let entries: Vec<Element<_>> = match self.tab() {
+            Tab::Posts => self
+                .statuses
+                .iter()
+                .filter_map(|id| cache.statuses.get(&id.to_string()))
+                .map(|status| {
+                    widgets::status(status, StatusOptions::all(), cache).map(Message::Status)
+                })
+                .collect(),
+            Tab::Hashtags => self.hashtags.iter().map(hashtag).collect(),
+            Tab::Links => self.links.iter().map(|l| link(l, cache)).collect(),
+        };
+        let end = match self.tab() {
+            Tab::Posts => self.end,
+            Tab::Hashtags | Tab::Links => !entries.is_empty(),
+        };
+
+        widget::column()
+            .push(header)
+            .push(widget::settings::section().extend(entries))
+            .push_maybe(super::footer(self.loading.is_some(), end))
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .id(super::SCROLLABLE.clone())
+            .direction(Direction::Vertical(
+                Scrollbar::default().spacing(spacing.space_xxs),
+            ))
+            .on_scroll(|viewport| Message::Scrolled(super::scrolled(viewport)))
+            .apply(widget::container)
+            .max_width(700)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let mut tasks = vec![];
+        match message {
+            Message::SetClient(mastodon) => self.mastodon = mastodon,
+            Message::SelectTab(entity) => {
+                self.tabs.activate(entity);
+                let empty = match self.tab() {
+                    Tab::Posts => self.statuses.is_empty(),
+                    Tab::Hashtags => self.hashtags.is_empty(),
+                    Tab::Links => self.links.is_empty(),
+                };
+                let latest = self.latest();
+                self.queued = None;
+                if empty {
+                    match self.loading {
+                        None => self.loading = Some(latest),
+                        Some(load) if mem::discriminant(&load) != mem::discriminant(&latest) => {
+                            self.queued = Some(latest)
+                        }
+                        Some(_) => (),
+                    }
+                }
+                tasks.push(cosmic::task::message(app::Message::Fetch(self.images())));
+            }
+            Message::Refresh => {
+                if self.loading.is_none() {
+                    self.loading = Some(self.latest());
+                }
+            }
+            Message::Scrolled(offset) => {
+                if self.loading.is_none() && !self.end && offset >= 1.0 && self.tab() == Tab::Posts
+                {
+                    self.loading = Some(Load::Posts(self.statuses.len()));
+                }
+            }
+            Message::PostsLoaded(offset, statuses) => {
+                self.loading = self.queued.take();
+                self.end = statuses.len() < api::PAGE_SIZE;
+                // Trends are reordered between requests, the first page replaces the list.
+                if offset == 0 {
+                    self.statuses.clear();
+                }
+                for status in statuses {
+                    if self.statuses.contains(&status.id) {
+                        continue;
+                    }
+                    self.statuses.push_back(status.id.clone());
+                    tasks.push(cosmic::task::message(app::Message::Fetch(
+                        utils::extract_status_images(&status),
+                    )));
+                    tasks.push(cosmic::task::message(app::Message::CacheStatus(status)));
+                }
+            }
+            Message::HashtagsLoaded(hashtags) => {
+                self.loading = self.queued.take();
+                self.hashtags = hashtags;
+            }
+            Message::LinksLoaded(links) => {
+                self.loading = self.queued.take();
+                self.links = links;
+                tasks.push(cosmic::task::message(app::Message::Fetch(self.images())));
+            }
+            Message::Load(load) => {
+                if self.loading.is_none() {
+                    self.loading = Some(load);
+                }
+            }
+            Message::LoadFailed(message) => {
+                let retry = self
+                    .loading
+                    .take()
+                    .map(|load| Box::new(app::Message::Explore(Message::Load(load))));
+                self.loading = self.queued.take();
+                tasks.push(cosmic::task::message(app::Message::Error(message, retry)));
+            }
+            Message::Restore(statuses) => {
+                if self.statuses.is_empty() {
+                    for status in statuses {
+                        self.statuses.push_back(status.id.clone());
+                        tasks.push(cosmic::task::message(app::Message::Fetch(
+                            utils::extract_status_images(&status),
+                        )));
+                        tasks.push(cosmic::task::message(app::Message::CacheStatus(status)));
+                    }
+                }
+            }
+            Message::OpenHashtag(name) => {
+                tasks.push(cosmic::task::message(app::Message::OpenHashtag(name)))
+            }
+            Message::OpenLink(url) => {
+                tasks.push(cosmic::task::message(app::Message::Open(url.to_string())))
+            }
+            Message::Status(message) => tasks.push(widgets::status::update(message)),
+        }
+        Task::batch(tasks)
+    }
+
+    fn tab(&self) -> Tab {
+        self.tabs
+            .active_data::<Tab>()
+            .copied()
+            .unwrap_or(Tab::Posts)
+    }
+
+    /// Loads the current trends of the active tab.
+    fn latest(&self) -> Load {
+        match self.tab() {
+            Tab::Posts => Load::Posts(0),
+            Tab::Hashtags => Load::Hashtags,
+            Tab::Links => Load::Links,
+        }
+    }
+
+    pub fn status_ids(&self) -> impl Iterator<Item = &StatusId> {
+        self.statuses.iter()
+    }
+
+    /// Preview images of the trending links, while their tab is open.
+    pub fn images(&self) -> Vec<(Url, images::Thumbnail)> {
+        if self.tab() != Tab::Links {
+            return vec![];
+        }
+        self.links
+            .iter()
+            .filter_map(|link| link.image.as_deref())
+            .filter_map(|image| Url::from_str(image).ok())
+            .map(|url| (url, images::Thumbnail::Preview))
+            .collect()
+    }
+
+    /// Drops the lower trending posts past `limit`, which are loaded again when scrolling down.
+    pub fn trim(&mut self, limit: usize) {
+        if self.loading.is_none() && self.statuses.len() > limit {
+            self.statuses.truncate(limit);
+            self.end = false;
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match self.loading {
+            Some(load) => crate::subscriptions::explore::trends(self.mastodon.clone(), load),
+            None => Subscription::none(),
+        }
+    }
+}
+
+fn hashtag(hashtag: &TrendingTag) -> Element<Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let accounts: u64 = hashtag.history.iter().map(|day| day.accounts).sum();
+
+    let content = widget::row()
+        .push(
+            widget::column()
+                .push(
+                    widget::button::link(format!("#{}", hashtag.name))
+                        .on_press(Message::OpenHashtag(hashtag.name.clone())),
+                )
+                .push(widget::text::caption(fl!(
+                    "people-talking",
+                    count = accounts,
+                    days = hashtag.history.len()
+                ))),
+        )
+        .push(widget::horizontal_space())
+        .push(sparkline(&hashtag.history))
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_xs);
+
+    widget::settings::flex_item_row(vec![content.into()])
+        .padding(spacing.space_xs)
+        .into()
+}
+
+fn link<'a>(link: &'a TrendingLink, cache: &'a Cache) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let shares: u64 = link.history.iter().map(|day| day.uses).sum();
+
+    let image = link
+        .image
+        .as_deref()
+        .and_then(|image| Url::from_str(image).ok())
+        .and_then(|url| cache.handle(&url))
+        .map(|handle| widget::image(handle).width(Length::Fill));
+
+    let text = widget::column()
+        .push_maybe(
+            (!link.provider_name.is_empty()).then(|| widget::text::caption(&link.provider_name)),
+        )
+        .push(widget::text::title4(&link.title))
+        .push(widget::text(&link.description))
+        .push(widget::text::caption(fl!(
+            "link-shares",
+            count = shares,
+            days = link.history.len()
+        )))
+        .spacing(spacing.space_xxs)
+        .padding(spacing.space_xs);
+
+    widget::column()
+        .push_maybe(image)
+        .push(text)
+        .apply(widget::container)
+        .class(cosmic::style::Container::Dialog)
+        .apply(widget::button::custom)
+        .class(cosmic::style::Button::Image)
+        .on_press(Message::OpenLink(link.url.clone()))
+        .apply(widget::container)
+        .padding(spacing.space_xs)
+        .into()
+}
+
+/// Daily uses as bars, oldest on the left.
+fn sparkline<'a>(history: &[History]) -> Element<'a, Message> {
+    let max = history.iter().map(|day| day.uses).max().unwrap_or(0).max(1);
+    let bars = history.iter().rev().map(|day| {
+        let height = 2.0 + (SPARKLINE_HEIGHT - 2.0) * day.uses as f32 / max as f32;
+        widget::container(widget::Space::new(6, height))
+            .class(cosmic::theme::Container::custom(|theme| {
+                widget::container::Style {
+                    background: Some(Background::Color(theme.cosmic().accent_color().into())),
+                    ..Default::default()
+                }
+            }))
+            .into()
+    });
+
+    widget::row()
+        .extend(bars)
+        .height(SPARKLINE_HEIGHT)
+        .align_y(Alignment::End)
+        .spacing(2)
+        .into()
+}
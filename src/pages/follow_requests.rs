@@ -0,0 +1,180 @@
+use cosmic::{
+    app::command::Task,
+    iced::{Length, Subscription},
+    iced_widget::scrollable::{Direction, Scrollbar},
+    widget, Apply, Element,
+};
+use mastodon_async::prelude::{Account, Mastodon};
+
+use crate::{
+    api::Pagination,
+    app, fl,
+    utils::{self, Cache},
+};
+
+use super::MastodonPage;
+
+/// Accounts waiting for their request to follow the user to be accepted.
+#[derive(Debug, Clone)]
+pub struct FollowRequests {
+    pub mastodon: Mastodon,
+    accounts: Vec<Account>,
+    loading: Option<Pagination>,
+    /// The `max_id` of the next page, `None` once every request is loaded.
+    next: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SetClient(Mastodon),
+    Refresh,
+    Load(Pagination),
+    Scrolled(f32),
+    /// A page of requests and the `max_id` of the page after it.
+    Loaded(Pagination, Vec<Account>, Option<String>),
+    LoadFailed(String),
+    OpenAccount(Account),
+    Accept(String),
+    Reject(String),
+    /// Drops the request of an account once it was accepted or rejected.
+    Remove(String),
+}
+
+impl MastodonPage for FollowRequests {
+    fn is_authenticated(&self) -> bool {
+        !self.mastodon.data.token.is_empty()
+    }
+}
+
+impl FollowRequests {
+    pub fn new(mastodon: Mastodon) -> Self {
+        Self {
+            mastodon,
+            accounts: vec![],
+            loading: Some(Pagination::Latest),
+            next: None,
+        }
+    }
+
+    pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let refreshing = matches!(self.loading, Some(Pagination::Latest));
+        let refresh = super::refresh_header(
+            refreshing,
+            self.loading.is_none().then_some(Message::Refresh),
+        );
+
+        let requests: Element<_> = if self.accounts.is_empty() && self.loading.is_none() {
+            super::empty(fl!("no-follow-requests"))
+        } else {
+            widget::settings::section()
+                .extend(self.accounts.iter().map(|account| request(account, cache)))
+                .into()
+        };
+
+        let footer = (!self.accounts.is_empty())
+            .then(|| super::footer(self.loading.is_some() && !refreshing, self.next.is_none()))
+            .flatten();
+
+        widget::column()
+            .push(refresh)
+            .push(requests)
+            .push_maybe(footer)
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .direction(Direction::Vertical(
+                Scrollbar::default().spacing(spacing.space_xxs),
+            ))
+            .on_scroll(|viewport| Message::Scrolled(super::scrolled(viewport)))
+            .apply(widget::container)
+            .max_width(700)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let mut tasks = vec![];
+        match message {
+            Message::SetClient(mastodon) => {
+                self.mastodon = mastodon;
+                self.loading = Some(Pagination::Latest);
+            }
+            Message::Refresh => self.loading = Some(Pagination::Latest),
+            Message::Load(pagination) => {
+                if self.loading.is_none() {
+                    self.loading = Some(pagination);
+                }
+            }
+            Message::Scrolled(offset) => {
+                if self.loading.is_none() && offset >= 1.0 {
+                    self.loading = self.next.clone().map(|max_id| Pagination::Older { max_id });
+                }
+            }
+            Message::Loaded(pagination, accounts, next) => {
+                self.loading = None;
+                self.next = next;
+                for account in &accounts {
+                    tasks.push(cosmic::task::message(app::Message::Fetch(
+                        utils::extract_account_images(account),
+                    )));
+                }
+                match pagination {
+                    Pagination::Older { .. } => {
+                        self.accounts
+                            .retain(|known| !accounts.iter().any(|account| account.id == known.id));
+                        self.accounts.extend(accounts);
+                    }
+                    _ => self.accounts = accounts,
+                }
+            }
+            Message::LoadFailed(message) => {
+                let retry = self.loading.take().map(|pagination| {
+                    Box::new(app::Message::FollowRequests(Message::Load(pagination)))
+                });
+                tasks.push(cosmic::task::message(app::Message::Error(message, retry)));
+            }
+            Message::OpenAccount(account) => {
+                tasks.push(cosmic::task::message(app::Message::ShowAccount(account)))
+            }
+            Message::Accept(id) => tasks.push(cosmic::task::message(
+                app::Message::ResolveFollowRequest(id, true),
+            )),
+            Message::Reject(id) => tasks.push(cosmic::task::message(
+                app::Message::ResolveFollowRequest(id, false),
+            )),
+            Message::Remove(id) => self.accounts.retain(|account| account.id.to_string() != id),
+        }
+        Task::batch(tasks)
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.iter()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.loading {
+            Some(pagination) if self.is_authenticated() => {
+                crate::subscriptions::follow_requests::pending(
+                    self.mastodon.clone(),
+                    pagination.clone(),
+                )
+            }
+            _ => Subscription::none(),
+        }
+    }
+}
+
+fn request<'a>(account: &'a Account, cache: &'a Cache) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let id = account.id.to_string();
+
+    let profile = super::profile(account, cache, Message::OpenAccount(account.clone()))
+        .push(widget::horizontal_space())
+        .push(widget::button::suggested(fl!("accept")).on_press(Message::Accept(id.clone())))
+        .push(widget::button::destructive(fl!("reject")).on_press(Message::Reject(id)));
+
+    widget::settings::flex_item_row(vec![profile.into()])
+        .padding(spacing.space_xs)
+        .into()
+}
@@ -2,36 +2,60 @@ use std::collections::VecDeque;
 
 use cosmic::{
     app::command::Task,
-    iced::{Length, Subscription},
+    iced::{Alignment, Length, Subscription},
     iced_widget::scrollable::{Direction, Scrollbar},
     widget, Apply, Element,
 };
 use mastodon_async::prelude::{Mastodon, Status, StatusId};
 
 use crate::{
-    app,
+    api::{self, Pagination},
+    app, fl,
     utils::{self, Cache},
     widgets::{self, status::StatusOptions},
 };
 
-use super::MastodonPage;
+use super::{MastodonPage, Page};
 
 #[derive(Debug, Clone)]
 pub struct Home {
     pub mastodon: Mastodon,
-    statuses: VecDeque<StatusId>,
-    skip: usize,
-    loading: bool,
+    entries: VecDeque<Entry>,
+    loading: Option<Pagination>,
+    /// The newest status that was read, from the markers synced between clients.
+    last_read: Option<String>,
+    /// Whether the newest statuses were loaded since the last failure, so an empty
+    /// timeline means nobody is followed yet.
+    loaded: bool,
+}
+
+/// A row of the timeline, ordered from newest to oldest.
+#[derive(Debug, Clone)]
+enum Entry {
+    Status(StatusId),
+    /// Statuses between two loaded statuses that haven't been fetched yet.
+    Gap {
+        max_id: String,
+        min_id: String,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SetClient(Mastodon),
-    AppendStatus(Status),
+    Load(Pagination),
+    Loaded(Pagination, Vec<Status>),
+    LoadFailed(String),
+    Restore(Vec<Status>),
+    Refresh,
+    Scrolled(f32),
     PrependStatus(Status),
     DeleteStatus(String),
+    SetLastRead(String),
+    MarkAllRead,
+    /// Opens the accounts suggested to follow, for an empty timeline.
+    FindPeople,
     Status(crate::widgets::status::Message),
-    LoadMore(bool),
 }
 
 impl MastodonPage for Home {
@@ -44,80 +68,411 @@ impl Home {
     pub fn new(mastodon: Mastodon) -> Self {
         Self {
             mastodon,
-            statuses: VecDeque::new(),
-            skip: 0,
-            loading: false,
+            entries: VecDeque::new(),
+            loading: Some(Pagination::Latest),
+            last_read: None,
+            loaded: false,
         }
     }
 
     pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
         let spacing = cosmic::theme::active().cosmic().spacing;
-        let statuses: Vec<Element<_>> = self
-            .statuses
+        let last_read = self.last_read.as_deref();
+        let divider = self
+            .entries
             .iter()
-            .filter_map(|id| cache.statuses.get(&id.to_string()))
-            .map(|status| {
-                crate::widgets::status(status, StatusOptions::all(), cache).map(Message::Status)
+            .position(|entry| {
+                matches!(entry, Entry::Status(id) if !super::is_unread(&id.to_string(), last_read))
+            })
+            .filter(|index| *index > 0 && last_read.is_some());
+        let entries: Vec<Element<_>> = self
+            .entries
+            .iter()
+            .enumerate()
+            .flat_map(|(index, entry)| {
+                let divider = (divider == Some(index)).then(super::last_read_divider);
+                divider.into_iter().chain(self.entry(entry, cache))
             })
             .collect();
 
-        widget::scrollable(widget::settings::section().extend(statuses))
+        let refresh = widget::row()
+            .push(widget::horizontal_space())
+            .push_maybe(
+                matches!(self.loading, Some(Pagination::Latest))
+                    .then(|| widget::text::caption(fl!("loading"))),
+            )
+            .push(
+                widget::button::icon(widget::icon::from_name("mail-read-symbolic"))
+                    .tooltip(fl!("mark-all-read"))
+                    .on_press_maybe((self.unread() > 0).then_some(Message::MarkAllRead)),
+            )
+            .push(
+                widget::button::icon(widget::icon::from_name("view-refresh-symbolic"))
+                    .tooltip(fl!("refresh"))
+                    .on_press_maybe(self.loading.is_none().then_some(Message::Refresh)),
+            )
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs);
+
+        let empty = (entries.is_empty() && self.loading.is_none() && self.loaded).then(|| {
+            widget::column()
+                .push(widget::text(fl!("empty-home")))
+                .push(widget::button::suggested(fl!("find-people")).on_press(Message::FindPeople))
+                .align_x(Alignment::Center)
+                .spacing(spacing.space_xs)
+                .apply(widget::container)
+                .center_x(Length::Fill)
+        });
+
+        widget::column()
+            .push(refresh)
+            .push_maybe(empty)
+            .push(widget::settings::section().extend(entries))
+            .push_maybe(super::footer(
+                matches!(self.loading, Some(Pagination::Older { .. })),
+                false,
+            ))
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .id(super::SCROLLABLE.clone())
             .direction(Direction::Vertical(
                 Scrollbar::default().spacing(spacing.space_xxs),
             ))
-            .on_scroll(|viewport| {
-                Message::LoadMore(!self.loading && viewport.relative_offset().y == 1.0)
-            })
+            .on_scroll(|viewport| Message::Scrolled(viewport.relative_offset().y))
             .apply(widget::container)
             .max_width(700)
             .height(Length::Fill)
             .into()
     }
 
+    fn entry<'a>(&'a self, entry: &'a Entry, cache: &'a Cache) -> Option<Element<'a, Message>> {
+        match entry {
+            Entry::Status(id) => cache.statuses.get(&id.to_string()).map(|status| {
+                crate::widgets::status(status, StatusOptions::all(), cache).map(Message::Status)
+            }),
+            Entry::Gap { max_id, min_id } => Some(
+                widget::button::text(fl!("load-missing-posts"))
+                    .on_press_maybe(self.loading.is_none().then(|| {
+                        Message::Load(Pagination::Between {
+                            max_id: max_id.clone(),
+                            min_id: min_id.clone(),
+                        })
+                    }))
+                    .apply(widget::container)
+                    .center_x(Length::Fill)
+                    .into(),
+            ),
+        }
+    }
+
     pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let unread = self.unread();
         let mut tasks = vec![];
         match message {
-            Message::SetClient(mastodon) => self.mastodon = mastodon,
-            Message::LoadMore(load) => {
-                if !self.loading && load {
-                    self.loading = true;
-                    self.skip += 20;
+            Message::SetClient(mastodon) => {
+                self.mastodon = mastodon;
+                self.loaded = false;
+            }
+            Message::Load(pagination) => {
+                if self.loading.is_none() {
+                    self.loading = Some(pagination);
                 }
             }
-            Message::AppendStatus(status) => {
-                self.loading = false;
-                self.statuses.push_back(status.id.clone());
-                tasks.push(cosmic::task::message(app::Message::CacheStatus(
-                    status.clone(),
-                )));
-
+            Message::Refresh => {
+                if self.loading.is_none() {
+                    self.loading = Some(Pagination::Latest);
+                }
+            }
+            Message::Scrolled(offset) => {
+                if self.loading.is_none() {
+                    if offset >= 1.0 {
+                        self.loading = self.oldest_id().map(|max_id| Pagination::Older { max_id });
+                    } else if offset <= 0.0 {
+                        self.loading = Some(Pagination::Latest);
+                    }
+                }
+            }
+            Message::Loaded(pagination, statuses) => {
+                self.loading = None;
+                if matches!(pagination, Pagination::Latest) {
+                    self.loaded = true;
+                }
+                for status in &statuses {
+                    tasks.push(cosmic::task::message(app::Message::CacheStatus(
+                        status.clone(),
+                    )));
+                    tasks.push(cosmic::task::message(app::Message::Fetch(
+                        utils::extract_status_images(status),
+                    )));
+                }
+                self.merge(
+                    pagination,
+                    statuses.into_iter().map(|status| status.id).collect(),
+                );
+            }
+            Message::LoadFailed(message) => {
+                let retry = self
+                    .loading
+                    .take()
+                    .map(|pagination| Box::new(app::Message::Home(Message::Load(pagination))));
+                self.loaded = false;
+                tasks.push(cosmic::task::message(app::Message::Error(message, retry)));
+            }
+            Message::Restore(statuses) => {
+                if self.entries.is_empty() {
+                    for status in statuses {
+                        self.insert(status.id.clone());
+                        tasks.push(cosmic::task::message(app::Message::Fetch(
+                            utils::extract_status_images(&status),
+                        )));
+                        tasks.push(cosmic::task::message(app::Message::CacheStatus(status)));
+                    }
+                }
+            }
+            Message::PrependStatus(status) => {
+                self.insert(status.id.clone());
                 tasks.push(cosmic::task::message(app::Message::Fetch(
                     utils::extract_status_images(&status),
                 )));
-            }
-            Message::PrependStatus(status) => {
-                self.loading = false;
-                self.statuses.push_front(status.id.clone());
                 tasks.push(cosmic::task::message(app::Message::CacheStatus(status)));
             }
-            Message::DeleteStatus(id) => self
-                .statuses
-                .retain(|status_id| *status_id.to_string() != id),
+            Message::DeleteStatus(id) => self.entries.retain(|entry| match entry {
+                Entry::Status(status_id) => *status_id.to_string() != id,
+                Entry::Gap { .. } => true,
+            }),
+            Message::SetLastRead(id) => self.last_read = Some(id),
+            Message::MarkAllRead => {
+                if let Some(newest) = self.status_ids().next().map(|id| id.to_string()) {
+                    self.last_read = Some(newest.clone());
+                    tasks.push(cosmic::task::message(app::Message::SaveMarker(
+                        api::Timeline::Home,
+                        newest,
+                    )));
+                }
+            }
+            Message::FindPeople => tasks.push(cosmic::task::message(app::Message::OpenPage(
+                Page::Suggestions,
+            ))),
             Message::Status(message) => tasks.push(widgets::status::update(message)),
         }
+        if self.unread() != unread {
+            tasks.push(cosmic::task::message(app::Message::UnreadChanged));
+        }
         Task::batch(tasks)
     }
 
+    /// Loaded statuses that came in since the timeline was last read.
+    pub fn unread(&self) -> usize {
+        let last_read = self.last_read.as_deref();
+        self.status_ids()
+            .filter(|id| super::is_unread(&id.to_string(), last_read))
+            .count()
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
-        if self.is_authenticated()
-            && (self.statuses.is_empty() || self.statuses.len() != self.skip + 20)
-        {
-            Subscription::batch(vec![crate::subscriptions::home::user_timeline(
-                self.mastodon.clone(),
-                self.skip,
-            )])
-        } else {
-            Subscription::none()
+        match &self.loading {
+            Some(pagination) if self.is_authenticated() => {
+                Subscription::batch(vec![crate::subscriptions::home::user_timeline(
+                    self.mastodon.clone(),
+                    pagination.clone(),
+                )])
+            }
+            _ => Subscription::none(),
         }
     }
+
+    pub fn status_ids(&self) -> impl Iterator<Item = &StatusId> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Status(id) => Some(id),
+            Entry::Gap { .. } => None,
+        })
+    }
+
+    /// Drops the oldest entries past `limit`, which are loaded again when scrolling down.
+    pub fn trim(&mut self, limit: usize) {
+        if self.loading.is_none() {
+            self.entries.truncate(limit);
+            if let Some(Entry::Gap { .. }) = self.entries.back() {
+                self.entries.pop_back();
+            }
+        }
+    }
+
+    /// Adds a fetched page to the timeline, leaving a gap where the page doesn't
+    /// connect to the statuses that were already loaded.
+    fn merge(&mut self, pagination: Pagination, mut ids: Vec<StatusId>) {
+        ids.sort_by(|a, b| api::compare_ids(&b.to_string(), &a.to_string()));
+        let full = ids.len() >= api::PAGE_SIZE;
+
+        match pagination {
+            Pagination::Latest | Pagination::Older { .. } => {
+                let connected = ids
+                    .last()
+                    .map_or(true, |oldest| self.position(&oldest.to_string()).is_some());
+                for id in &ids {
+                    self.insert(id.clone());
+                }
+                if let (true, false, Some(oldest)) = (full, connected, ids.last()) {
+                    let oldest = oldest.to_string();
+                    if let Some(index) = self.position(&oldest) {
+                        if let Some(Entry::Status(below)) = self.entries.get(index + 1) {
+                            let gap = Entry::Gap {
+                                max_id: oldest,
+                                min_id: below.to_string(),
+                            };
+                            self.entries.insert(index + 1, gap);
+                        }
+                    }
+                }
+            }
+            Pagination::Between { max_id, min_id } => {
+                let Some(index) = self.entries.iter().position(|entry| {
+                    matches!(entry, Entry::Gap { max_id: gap_max, min_id: gap_min }
+                        if *gap_max == max_id && *gap_min == min_id)
+                }) else {
+                    return;
+                };
+                self.entries.remove(index);
+                for id in ids.iter().rev() {
+                    if self.position(&id.to_string()).is_none() {
+                        self.entries.insert(index, Entry::Status(id.clone()));
+                    }
+                }
+                if let (true, Some(newest)) = (full, ids.first()) {
+                    let newest = newest.to_string();
+                    if newest != max_id {
+                        let gap = Entry::Gap {
+                            max_id,
+                            min_id: newest,
+                        };
+                        self.entries.insert(index, gap);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Inserts a status in id order, ignoring statuses that are already loaded.
+    fn insert(&mut self, id: StatusId) {
+        let key = id.to_string();
+        if self.position(&key).is_some() {
+            return;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| match entry {
+                Entry::Status(other) => api::compare_ids(&other.to_string(), &key).is_lt(),
+                Entry::Gap { min_id, .. } => api::compare_ids(min_id, &key).is_lt(),
+            })
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, Entry::Status(id));
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.entries.iter().position(
+            |entry| matches!(entry, Entry::Status(status_id) if status_id.to_string() == id),
+        )
+    }
+
+    fn oldest_id(&self) -> Option<String> {
+        self.entries.iter().rev().find_map(|entry| match entry {
+            Entry::Status(id) => Some(id.to_string()),
+            Entry::Gap { .. } => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mastodon_async::Data;
+
+    use super::*;
+
+    fn ids(range: std::ops::RangeInclusive<u32>) -> Vec<StatusId> {
+        range.map(|id| StatusId::new(id.to_string())).collect()
+    }
+
+    /// The timeline as text, newest first, with gaps as `max..min`.
+    fn rows(home: &Home) -> Vec<String> {
+        home.entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Status(id) => id.to_string(),
+                Entry::Gap { max_id, min_id } => format!("{}..{}", max_id, min_id),
+            })
+            .collect()
+    }
+
+    fn home(loaded: std::ops::RangeInclusive<u32>) -> Home {
+        let mut home = Home::new(Mastodon::from(Data::default()));
+        for id in ids(loaded) {
+            home.insert(id);
+        }
+        home
+    }
+
+    fn descending(range: std::ops::RangeInclusive<u32>) -> Vec<String> {
+        range.rev().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn full_page_that_does_not_connect_leaves_a_gap() {
+        let mut home = home(49..=50);
+        home.merge(Pagination::Latest, ids(81..=100));
+
+        let mut want = descending(81..=100);
+        want.push("81..50".to_string());
+        want.extend(descending(49..=50));
+        assert_eq!(rows(&home), want);
+    }
+
+    #[test]
+    fn page_that_connects_leaves_no_gap() {
+        let mut home = home(49..=85);
+        home.merge(Pagination::Latest, ids(81..=100));
+
+        assert_eq!(rows(&home), descending(49..=100));
+    }
+
+    #[test]
+    fn short_page_leaves_no_gap() {
+        let mut home = home(49..=50);
+        home.merge(Pagination::Latest, ids(91..=100));
+
+        let mut want = descending(91..=100);
+        want.extend(descending(49..=50));
+        assert_eq!(rows(&home), want);
+    }
+
+    #[test]
+    fn gap_shrinks_and_closes_as_it_is_filled() {
+        let mut home = home(49..=50);
+        home.merge(Pagination::Latest, ids(81..=100));
+
+        let between = |max_id: &str, min_id: &str| Pagination::Between {
+            max_id: max_id.to_string(),
+            min_id: min_id.to_string(),
+        };
+        home.merge(between("81", "50"), ids(51..=70));
+        let mut want = descending(81..=100);
+        want.push("81..70".to_string());
+        want.extend(descending(49..=70));
+        assert_eq!(rows(&home), want);
+
+        home.merge(between("81", "70"), ids(71..=80));
+        assert_eq!(rows(&home), descending(49..=100));
+    }
+
+    #[test]
+    fn page_for_an_unknown_gap_is_ignored() {
+        let mut home = home(49..=50);
+        let pagination = Pagination::Between {
+            max_id: "81".to_string(),
+            min_id: "50".to_string(),
+        };
+        home.merge(pagination, ids(51..=70));
+
+        assert_eq!(rows(&home), descending(49..=50));
+    }
 }
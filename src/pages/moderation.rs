@@ -0,0 +1,334 @@
+use cosmic::{
+    app::command::Task,
+    iced::{Length, Subscription},
+    iced_widget::scrollable::{Direction, Scrollbar},
+    widget, Apply, Element,
+};
+use mastodon_async::prelude::{Account, Mastodon};
+use reqwest::Url;
+
+use crate::{
+    api::{self, AccountAction, AdminAccount, Report},
+    app, fl, images,
+    utils::{self, Cache},
+    widgets::{self, status::StatusOptions},
+};
+
+use super::MastodonPage;
+
+/// Open reports and pending sign-ups, for accounts with admin scopes.
+#[derive(Debug, Clone)]
+pub struct Moderation {
+    pub mastodon: Mastodon,
+    reports: Vec<Report>,
+    sign_ups: Vec<AdminAccount>,
+    loading: bool,
+    /// Whether the access token has `admin:write`, which acting on reports and sign-ups takes.
+    writable: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SetClient(Mastodon),
+    SetWritable(bool),
+    Refresh,
+    Loaded(Vec<Report>, Vec<AdminAccount>),
+    LoadFailed(String),
+    Status(widgets::status::Message),
+    OpenAccount(Account),
+    /// Resolves a report by its id without acting on the reported account.
+    Resolve(String),
+    /// Asks before acting on the account reported in a report, by report id.
+    Confirm(String, AccountAction),
+    /// Acts on the account reported in a report, by report id.
+    Moderate(String, AccountAction),
+    Approve(String),
+    Reject(String),
+    /// Drops a report once it was resolved.
+    ReportClosed(String),
+    /// Drops a sign-up once it was approved or rejected.
+    SignUpClosed(String),
+}
+
+impl MastodonPage for Moderation {
+    fn is_authenticated(&self) -> bool {
+        !self.mastodon.data.token.is_empty()
+    }
+}
+
+impl Moderation {
+    pub fn new(mastodon: Mastodon) -> Self {
+        Self {
+            mastodon,
+            reports: vec![],
+            sign_ups: vec![],
+            loading: true,
+            writable: false,
+        }
+    }
+
+    pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let refresh =
+            super::refresh_header(self.loading, (!self.loading).then_some(Message::Refresh));
+
+        let reports: Element<_> = if self.reports.is_empty() && !self.loading {
+            super::empty(fl!("no-reports"))
+        } else {
+            widget::column()
+                .extend(self.reports.iter().map(|r| report(r, self.writable, cache)))
+                .spacing(spacing.space_xs)
+                .into()
+        };
+
+        let sign_ups: Element<_> = if self.sign_ups.is_empty() && !self.loading {
+            super::empty(fl!("no-sign-ups"))
+        } else {
+            widget::settings::section()
+                .extend(
+                    self.sign_ups
+                        .iter()
+                        .map(|account| sign_up(account, self.writable, cache)),
+                )
+                .into()
+        };
+
+        widget::column()
+            .push(refresh)
+            .push(widget::text::heading(fl!("open-reports")))
+            .push(reports)
+            .push(widget::text::heading(fl!("pending-sign-ups")))
+            .push(sign_ups)
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .direction(Direction::Vertical(
+                Scrollbar::default().spacing(spacing.space_xxs),
+            ))
+            .apply(widget::container)
+            .max_width(700)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let mut tasks = vec![];
+        match message {
+            Message::SetClient(mastodon) => {
+                self.mastodon = mastodon;
+                self.loading = true;
+            }
+            Message::SetWritable(writable) => self.writable = writable,
+            Message::Refresh => self.loading = true,
+            Message::Loaded(reports, sign_ups) => {
+                self.loading = false;
+                tasks.push(cosmic::task::message(app::Message::Fetch(
+                    images(&reports, &sign_ups).collect(),
+                )));
+                self.reports = reports;
+                self.sign_ups = sign_ups;
+            }
+            Message::LoadFailed(message) => {
+                self.loading = false;
+                tasks.push(cosmic::task::message(app::Message::Error(
+                    message,
+                    Some(Box::new(app::Message::Moderation(Message::Refresh))),
+                )));
+            }
+            Message::Status(message) => tasks.push(widgets::status::update(message)),
+            Message::OpenAccount(account) => {
+                tasks.push(cosmic::task::message(app::Message::ShowAccount(account)))
+            }
+            Message::Resolve(id) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::resolve_report(&mastodon, &id).await {
+                        Ok(()) => app::Message::Moderation(Message::ReportClosed(id)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Moderation(Message::Resolve(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::Confirm(id, action) => tasks.push(cosmic::task::message(
+                app::Message::Dialog(app::DialogAction::Open(app::Dialog::Moderate(id, action))),
+            )),
+            Message::Moderate(id, action) => {
+                let Some(report) = self.reports.iter().find(|report| report.id == id) else {
+                    return Task::none();
+                };
+                let mastodon = self.mastodon.clone();
+                let account = report.target_account.id.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::moderate(&mastodon, &account, action, &id).await {
+                        Ok(()) => app::Message::Moderation(Message::ReportClosed(id)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            let retry = app::Message::Moderation(Message::Moderate(id, action));
+                            app::Message::Error(err.user_message(), Some(Box::new(retry)))
+                        }
+                    }
+                }));
+            }
+            Message::Approve(id) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::approve_account(&mastodon, &id).await {
+                        Ok(()) => app::Message::Moderation(Message::SignUpClosed(id)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Moderation(Message::Approve(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::Reject(id) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::reject_account(&mastodon, &id).await {
+                        Ok(()) => app::Message::Moderation(Message::SignUpClosed(id)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Moderation(Message::Reject(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::ReportClosed(id) => self.reports.retain(|report| report.id != id),
+            Message::SignUpClosed(id) => self.sign_ups.retain(|account| account.id != id),
+        }
+        Task::batch(tasks)
+    }
+
+    /// Images of the reported accounts and statuses and of the pending sign-ups.
+    pub fn images(&self) -> Vec<(Url, images::Thumbnail)> {
+        images(&self.reports, &self.sign_ups).collect()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.loading && self.is_authenticated() {
+            crate::subscriptions::moderation::queue(self.mastodon.clone())
+        } else {
+            Subscription::none()
+        }
+    }
+}
+
+fn images<'a>(
+    reports: &'a [Report],
+    sign_ups: &'a [AdminAccount],
+) -> impl Iterator<Item = (Url, images::Thumbnail)> + 'a {
+    reports
+        .iter()
+        .flat_map(|report| {
+            utils::extract_account_images(&report.target_account.account)
+                .into_iter()
+                .chain(
+                    report
+                        .statuses
+                        .iter()
+                        .flat_map(utils::extract_status_images),
+                )
+        })
+        .chain(
+            sign_ups
+                .iter()
+                .flat_map(|account| utils::extract_account_images(&account.account)),
+        )
+}
+
+/// The avatar and names of an account, opening its profile when clicked.
+fn profile<'a>(account: &'a Account, cache: &'a Cache) -> widget::Row<'a, Message> {
+    super::profile(account, cache, Message::OpenAccount(account.clone()))
+}
+
+fn report<'a>(report: &'a Report, writable: bool, cache: &'a Cache) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let id = &report.id;
+
+    let reporter = fl!(
+        "reported-by",
+        acct = report.account.account.acct.as_str(),
+        category = report.category.as_deref().unwrap_or("other")
+    );
+
+    let statuses = report.statuses.iter().map(|status| {
+        widget::container(
+            widgets::status(status, StatusOptions::new(false, true, false, false), cache)
+                .map(Message::Status),
+        )
+        .padding(spacing.space_xxs)
+        .class(cosmic::theme::Container::Dialog)
+        .into()
+    });
+
+    let actions = widget::flex_row(vec![
+        widget::button::standard(fl!("resolve"))
+            .on_press_maybe(writable.then(|| Message::Resolve(id.clone())))
+            .into(),
+        widget::button::standard(fl!("mark-sensitive"))
+            .on_press_maybe(
+                writable.then(|| Message::Confirm(id.clone(), AccountAction::Sensitive)),
+            )
+            .into(),
+        widget::button::standard(fl!("silence"))
+            .on_press_maybe(writable.then(|| Message::Confirm(id.clone(), AccountAction::Silence)))
+            .into(),
+        widget::button::destructive(fl!("suspend"))
+            .on_press_maybe(writable.then(|| Message::Confirm(id.clone(), AccountAction::Suspend)))
+            .into(),
+    ])
+    .column_spacing(spacing.space_xs)
+    .row_spacing(spacing.space_xs);
+
+    let content = widget::column()
+        .push(profile(&report.target_account.account, cache))
+        .push(widget::text::caption(reporter))
+        .push_maybe((!report.comment.is_empty()).then(|| widget::text(&report.comment)))
+        .extend(statuses)
+        .push(actions)
+        .spacing(spacing.space_xs);
+
+    widget::settings::section()
+        .add(widget::settings::flex_item_row(vec![content.into()]).padding(spacing.space_xs))
+        .into()
+}
+
+fn sign_up<'a>(
+    account: &'a AdminAccount,
+    writable: bool,
+    cache: &'a Cache,
+) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+
+    let row = profile(&account.account, cache)
+        .push(widget::horizontal_space())
+        .push(
+            widget::button::suggested(fl!("approve"))
+                .on_press_maybe(writable.then(|| Message::Approve(account.id.clone()))),
+        )
+        .push(
+            widget::button::destructive(fl!("reject"))
+                .on_press_maybe(writable.then(|| Message::Reject(account.id.clone()))),
+        );
+
+    let content = widget::column()
+        .push(row)
+        .push_maybe(account.email.as_deref().map(widget::text::caption))
+        .push_maybe(account.invite_request.as_deref().map(widget::text))
+        .spacing(spacing.space_xs);
+
+    widget::settings::flex_item_row(vec![content.into()])
+        .padding(spacing.space_xs)
+        .into()
+}
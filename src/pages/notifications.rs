@@ -1,18 +1,19 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use cosmic::{
     app::command::Task,
-    iced::{Length, Subscription},
+    iced::{Alignment, Length, Subscription},
     iced_widget::scrollable::{Direction, Scrollbar},
     widget, Apply, Element,
 };
 use mastodon_async::{
-    entities::notification::Notification,
+    entities::notification::{Notification, Type},
     prelude::{Mastodon, NotificationId},
 };
 
 use crate::{
-    app,
+    api::{self, Pagination},
+    app, fl,
     utils::{self, Cache},
     widgets,
 };
@@ -23,13 +24,90 @@ use super::MastodonPage;
 pub struct Notifications {
     pub mastodon: Mastodon,
     notifications: VecDeque<NotificationId>,
+    loading: Option<Pagination>,
+    end: bool,
+    /// The newest notification that was read, from the markers synced between clients.
+    last_read: Option<String>,
+    /// The types shown, all of them while empty.
+    filters: HashSet<Filter>,
+}
+
+/// Notification types the page can be narrowed down to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Filter {
+    Mentions,
+    Boosts,
+    Favourites,
+    Follows,
+    Polls,
+    Edits,
+    Admin,
+}
+
+impl Filter {
+    const ALL: [Filter; 7] = [
+        Filter::Mentions,
+        Filter::Boosts,
+        Filter::Favourites,
+        Filter::Follows,
+        Filter::Polls,
+        Filter::Edits,
+        Filter::Admin,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            Filter::Mentions => fl!("filter-mentions"),
+            Filter::Boosts => fl!("filter-boosts"),
+            Filter::Favourites => fl!("filter-favourites"),
+            Filter::Follows => fl!("filter-follows"),
+            Filter::Polls => fl!("filter-polls"),
+            Filter::Edits => fl!("filter-edits"),
+            Filter::Admin => fl!("filter-admin"),
+        }
+    }
+
+    fn matches(self, notification_type: &Type) -> bool {
+        match self {
+            Filter::Mentions => matches!(notification_type, Type::Mention | Type::Status),
+            Filter::Boosts => matches!(notification_type, Type::Reblog),
+            Filter::Favourites => matches!(notification_type, Type::Favourite),
+            Filter::Follows => matches!(notification_type, Type::Follow | Type::FollowRequest),
+            Filter::Polls => matches!(notification_type, Type::Poll),
+            Filter::Edits => matches!(notification_type, Type::Update),
+            Filter::Admin => matches!(notification_type, Type::SignUp | Type::Report),
+        }
+    }
+
+    /// The names of the types in the API, as sent in `types[]`.
+    fn types(self) -> &'static [&'static str] {
+        match self {
+            Filter::Mentions => &["mention", "status"],
+            Filter::Boosts => &["reblog"],
+            Filter::Favourites => &["favourite"],
+            Filter::Follows => &["follow", "follow_request"],
+            Filter::Polls => &["poll"],
+            Filter::Edits => &["update"],
+            Filter::Admin => &["admin.sign_up", "admin.report"],
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SetClient(Mastodon),
-    AppendNotification(Notification),
+    Load(Pagination),
+    Loaded(Pagination, Vec<Notification>),
+    LoadFailed(String),
+    Restore(Vec<Notification>),
+    Scrolled(f32),
     PrependNotification(Notification),
+    SetLastRead(String),
+    MarkAllRead,
+    ToggleFilter(Filter),
+    /// Drops notifications that were resolved, like answered follow requests.
+    RemoveNotifications(Vec<NotificationId>),
+    ClearFilters,
     Notification(crate::widgets::notification::Message),
 }
 
@@ -44,24 +122,79 @@ impl Notifications {
         Self {
             mastodon,
             notifications: VecDeque::new(),
+            loading: Some(Pagination::Latest),
+            end: false,
+            last_read: None,
+            filters: HashSet::new(),
         }
     }
 
     pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
         let spacing = cosmic::theme::active().cosmic().spacing;
-        let notifications: Vec<Element<_>> = self
-            .notifications
+        let last_read = self.last_read.as_deref();
+        let groups = self.groups(cache);
+        let divider = groups
             .iter()
-            .filter_map(|id| cache.notifications.get(&id.to_string()))
-            .map(|notification| {
-                crate::widgets::notification(notification, cache).map(Message::Notification)
+            .position(|group| !super::is_unread(&group[0].id.to_string(), last_read))
+            .filter(|index| *index > 0 && last_read.is_some());
+        let notifications: Vec<Element<_>> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, group)| {
+                let divider = (divider == Some(index)).then(super::last_read_divider);
+                let notification =
+                    crate::widgets::notification(group, cache).map(Message::Notification);
+                divider.into_iter().chain([notification])
             })
             .collect();
 
-        widget::scrollable(widget::settings::section().extend(notifications))
+        let chip = |label: String, active: bool, message: Message| -> Element<'a, Message> {
+            let button = if active {
+                widget::button::suggested(label)
+            } else {
+                widget::button::standard(label)
+            };
+            button.on_press(message).into()
+        };
+        let filters = widget::flex_row(
+            std::iter::once(chip(
+                fl!("all"),
+                self.filters.is_empty(),
+                Message::ClearFilters,
+            ))
+            .chain(Filter::ALL.into_iter().map(|filter| {
+                chip(
+                    filter.label(),
+                    self.filters.contains(&filter),
+                    Message::ToggleFilter(filter),
+                )
+            }))
+            .collect(),
+        )
+        .column_spacing(spacing.space_xxs)
+        .row_spacing(spacing.space_xxs);
+
+        let header = widget::row()
+            .push(filters)
+            .push(widget::horizontal_space())
+            .push(
+                widget::button::icon(widget::icon::from_name("mail-read-symbolic"))
+                    .tooltip(fl!("mark-all-read"))
+                    .on_press_maybe((self.unread() > 0).then_some(Message::MarkAllRead)),
+            )
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs);
+
+        widget::column()
+            .push(header)
+            .push(widget::settings::section().extend(notifications))
+            .push_maybe(super::footer(self.loading.is_some(), self.end))
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
             .direction(Direction::Vertical(
                 Scrollbar::default().spacing(spacing.space_xxs),
             ))
+            .on_scroll(|viewport| Message::Scrolled(super::scrolled(viewport)))
             .apply(widget::container)
             .max_width(700)
             .height(Length::Fill)
@@ -69,41 +202,213 @@ impl Notifications {
     }
 
     pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let unread = self.unread();
         let mut tasks = vec![];
         match message {
             Message::SetClient(mastodon) => self.mastodon = mastodon,
-            Message::AppendNotification(notification) => {
-                self.notifications.push_back(notification.id.clone());
-                tasks.push(cosmic::task::message(app::Message::CacheNotification(
-                    notification.clone(),
-                )));
-
+            Message::Scrolled(offset) => {
+                if self.loading.is_none() && !self.end && offset >= 1.0 {
+                    self.loading = self.notifications.back().map(|id| Pagination::Older {
+                        max_id: id.to_string(),
+                    });
+                }
+            }
+            Message::Loaded(pagination, notifications) => {
+                self.loading = None;
+                if matches!(pagination, Pagination::Older { .. }) || self.notifications.is_empty() {
+                    self.end = notifications.len() < api::PAGE_SIZE;
+                }
+                let mut index = 0;
+                for notification in notifications {
+                    if self.notifications.contains(&notification.id) {
+                        continue;
+                    }
+                    match pagination {
+                        Pagination::Older { .. } => {
+                            self.notifications.push_back(notification.id.clone())
+                        }
+                        _ => {
+                            self.notifications.insert(index, notification.id.clone());
+                            index += 1;
+                        }
+                    }
+                    tasks.push(cosmic::task::message(app::Message::Fetch(
+                        utils::extract_notification_images(&notification),
+                    )));
+                    tasks.push(cosmic::task::message(app::Message::CacheNotification(
+                        notification,
+                    )));
+                }
+            }
+            Message::Load(pagination) => {
+                if self.loading.is_none() {
+                    self.loading = Some(pagination);
+                }
+            }
+            Message::LoadFailed(message) => {
+                let retry = self.loading.take().map(|pagination| {
+                    Box::new(app::Message::Notifications(Message::Load(pagination)))
+                });
+                tasks.push(cosmic::task::message(app::Message::Error(message, retry)));
+            }
+            Message::Restore(notifications) => {
+                if self.notifications.is_empty() {
+                    for notification in notifications {
+                        self.notifications.push_back(notification.id.clone());
+                        tasks.push(cosmic::task::message(app::Message::Fetch(
+                            utils::extract_notification_images(&notification),
+                        )));
+                        tasks.push(cosmic::task::message(app::Message::CacheNotification(
+                            notification,
+                        )));
+                    }
+                }
+            }
+            Message::PrependNotification(notification) => {
+                if !self.notifications.contains(&notification.id) {
+                    self.notifications.push_front(notification.id.clone());
+                }
                 tasks.push(cosmic::task::message(app::Message::Fetch(
                     utils::extract_notification_images(&notification),
                 )));
-            }
-            Message::PrependNotification(notification) => {
-                self.notifications.push_front(notification.id.clone());
                 tasks.push(cosmic::task::message(app::Message::CacheNotification(
                     notification,
                 )));
             }
+            Message::SetLastRead(id) => self.last_read = Some(id),
+            Message::MarkAllRead => {
+                if let Some(newest) = self.notifications.front().map(|id| id.to_string()) {
+                    self.last_read = Some(newest.clone());
+                    tasks.push(cosmic::task::message(app::Message::SaveMarker(
+                        api::Timeline::Notifications,
+                        newest,
+                    )));
+                }
+            }
+            Message::ToggleFilter(filter) => {
+                if !self.filters.remove(&filter) {
+                    self.filters.insert(filter);
+                }
+                self.reload();
+            }
+            Message::ClearFilters => {
+                if !self.filters.is_empty() {
+                    self.filters.clear();
+                    self.reload();
+                }
+            }
+            Message::RemoveNotifications(ids) => {
+                self.notifications.retain(|id| !ids.contains(id));
+            }
             Message::Notification(message) => match message {
                 crate::widgets::notification::Message::Status(message) => {
                     tasks.push(widgets::status::update(message))
                 }
+                crate::widgets::notification::Message::AcceptFollowRequest(id) => tasks.push(
+                    cosmic::task::message(app::Message::ResolveFollowRequest(id, true)),
+                ),
+                crate::widgets::notification::Message::RejectFollowRequest(id) => tasks.push(
+                    cosmic::task::message(app::Message::ResolveFollowRequest(id, false)),
+                ),
+                crate::widgets::notification::Message::OpenModeration => {
+                    tasks.push(cosmic::task::message(app::Message::OpenModeration))
+                }
             },
         }
+        if self.unread() != unread {
+            tasks.push(cosmic::task::message(app::Message::UnreadChanged));
+        }
         Task::batch(tasks)
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        if self.is_authenticated() && self.notifications.is_empty() {
-            return Subscription::batch(vec![crate::subscriptions::notifications::timeline(
-                self.mastodon.clone(),
-            )]);
+    /// The notifications that pass the filters, newest first, with boosts and favourites
+    /// of the same status folded into a group at the position of the newest one.
+    fn groups<'a>(&self, cache: &'a Cache) -> Vec<Vec<&'a Notification>> {
+        let mut groups: Vec<Vec<&Notification>> = vec![];
+        // Group indices by whether the group holds boosts or favourites, and the status.
+        let mut grouped: HashMap<(bool, String), usize> = HashMap::new();
+        let notifications = self
+            .notifications
+            .iter()
+            .filter_map(|id| cache.notifications.get(&id.to_string()))
+            .filter(|notification| {
+                self.filters.is_empty()
+                    || self
+                        .filters
+                        .iter()
+                        .any(|filter| filter.matches(&notification.notification_type))
+            });
+        for notification in notifications {
+            let key = match (&notification.notification_type, &notification.status) {
+                (Type::Reblog, Some(status)) => Some((true, status.id.to_string())),
+                (Type::Favourite, Some(status)) => Some((false, status.id.to_string())),
+                _ => None,
+            };
+            match key {
+                Some(key) => match grouped.get(&key) {
+                    Some(index) => groups[*index].push(notification),
+                    None => {
+                        grouped.insert(key, groups.len());
+                        groups.push(vec![notification]);
+                    }
+                },
+                None => groups.push(vec![notification]),
+            }
+        }
+        groups
+    }
+
+    /// Loaded notifications that came in since the notifications were last read.
+    pub fn unread(&self) -> usize {
+        let last_read = self.last_read.as_deref();
+        self.notifications
+            .iter()
+            .filter(|id| super::is_unread(&id.to_string(), last_read))
+            .count()
+    }
+
+    pub fn notification_ids(&self) -> impl Iterator<Item = &NotificationId> {
+        self.notifications.iter()
+    }
+
+    /// Drops the oldest entries past `limit`, which are loaded again when scrolling down.
+    pub fn trim(&mut self, limit: usize) {
+        if self.loading.is_none() && self.notifications.len() > limit {
+            self.notifications.truncate(limit);
+            self.end = false;
         }
+    }
 
-        Subscription::none()
+    /// Loads the notifications again for the selected filters, which the server applies
+    /// so that a page of rare types isn't mostly hidden. A load in flight for the
+    /// previous filters is dropped along with its subscription.
+    fn reload(&mut self) {
+        self.notifications.clear();
+        self.end = false;
+        self.loading = Some(Pagination::Latest);
+    }
+
+    /// The API types of the selected filters, empty for all of them.
+    fn types(&self) -> Vec<&'static str> {
+        let mut types: Vec<&'static str> = self
+            .filters
+            .iter()
+            .flat_map(|filter| filter.types().iter().copied())
+            .collect();
+        types.sort_unstable();
+        types
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.loading {
+            Some(pagination) if self.is_authenticated() => {
+                Subscription::batch(vec![crate::subscriptions::notifications::timeline(
+                    self.mastodon.clone(),
+                    pagination.clone(),
+                    self.types(),
+                )])
+            }
+            _ => Subscription::none(),
+        }
     }
 }
@@ -9,6 +9,7 @@ use cosmic::{
 use mastodon_async::prelude::{Mastodon, Status, StatusId};
 
 use crate::{
+    api::{self, Pagination},
     app,
     utils::Cache,
     widgets::{self, status::StatusOptions},
@@ -21,19 +22,27 @@ pub struct Public {
     pub mastodon: Mastodon,
     statuses: VecDeque<StatusId>,
     timeline: TimelineType,
+    loading: Option<Pagination>,
+    end: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum TimelineType {
-    Public,
     Local,
     Remote,
+    /// Public statuses tagged with a hashtag, without the `#`.
+    Hashtag(String),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SetClient(Mastodon),
-    AppendStatus(Status),
+    Load(Pagination),
+    Loaded(Pagination, Vec<Status>),
+    LoadFailed(String),
+    Restore(Vec<Status>),
+    Scrolled(f32),
+    DeleteStatus(String),
     Status(crate::widgets::status::Message),
 }
 
@@ -49,6 +58,8 @@ impl Public {
             mastodon,
             statuses: VecDeque::new(),
             timeline,
+            loading: Some(Pagination::Latest),
+            end: false,
         }
     }
 
@@ -63,10 +74,22 @@ impl Public {
             })
             .collect();
 
-        widget::scrollable(widget::settings::section().extend(statuses))
+        let title = match &self.timeline {
+            TimelineType::Hashtag(hashtag) => Some(widget::text::title3(format!("#{}", hashtag))),
+            _ => None,
+        };
+
+        widget::column()
+            .push_maybe(title)
+            .push(widget::settings::section().extend(statuses))
+            .push_maybe(super::footer(self.loading.is_some(), self.end))
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .id(super::SCROLLABLE.clone())
             .direction(Direction::Vertical(
                 Scrollbar::default().spacing(spacing.space_xxs),
             ))
+            .on_scroll(|viewport| Message::Scrolled(super::scrolled(viewport)))
             .apply(widget::container)
             .max_width(700)
             .height(Length::Fill)
@@ -77,42 +100,94 @@ impl Public {
         let mut tasks = vec![];
         match message {
             Message::SetClient(mastodon) => self.mastodon = mastodon,
-            Message::AppendStatus(status) => {
-                self.statuses.push_back(status.id.clone());
-                tasks.push(cosmic::task::message(app::Message::CacheStatus(
-                    status.clone(),
-                )));
-
-                tasks.push(cosmic::task::message(app::Message::Fetch(
-                    crate::utils::extract_status_images(&status),
-                )));
+            Message::Scrolled(offset) => {
+                if self.loading.is_none() && !self.end && offset >= 1.0 {
+                    self.loading = self.statuses.back().map(|id| Pagination::Older {
+                        max_id: id.to_string(),
+                    });
+                }
+            }
+            Message::Loaded(pagination, statuses) => {
+                self.loading = None;
+                if matches!(pagination, Pagination::Older { .. }) || self.statuses.is_empty() {
+                    self.end = statuses.len() < api::PAGE_SIZE;
+                }
+                let mut index = 0;
+                for status in statuses {
+                    if self.statuses.contains(&status.id) {
+                        continue;
+                    }
+                    match pagination {
+                        Pagination::Older { .. } => self.statuses.push_back(status.id.clone()),
+                        _ => {
+                            self.statuses.insert(index, status.id.clone());
+                            index += 1;
+                        }
+                    }
+                    tasks.push(cosmic::task::message(app::Message::Fetch(
+                        crate::utils::extract_status_images(&status),
+                    )));
+                    tasks.push(cosmic::task::message(app::Message::CacheStatus(status)));
+                }
+            }
+            Message::Load(pagination) => {
+                if self.loading.is_none() {
+                    self.loading = Some(pagination);
+                }
+            }
+            Message::LoadFailed(message) => {
+                let retry = self
+                    .loading
+                    .take()
+                    .map(|pagination| Box::new(self.app_message(Message::Load(pagination))));
+                tasks.push(cosmic::task::message(app::Message::Error(message, retry)));
+            }
+            Message::Restore(statuses) => {
+                if self.statuses.is_empty() {
+                    for status in statuses {
+                        self.statuses.push_back(status.id.clone());
+                        tasks.push(cosmic::task::message(app::Message::Fetch(
+                            crate::utils::extract_status_images(&status),
+                        )));
+                        tasks.push(cosmic::task::message(app::Message::CacheStatus(status)));
+                    }
+                }
             }
+            Message::DeleteStatus(id) => self.statuses.retain(|status| *status.to_string() != id),
             Message::Status(message) => tasks.push(widgets::status::update(message)),
         }
         Task::batch(tasks)
     }
 
+    pub fn status_ids(&self) -> impl Iterator<Item = &StatusId> {
+        self.statuses.iter()
+    }
+
+    /// Drops the oldest entries past `limit`, which are loaded again when scrolling down.
+    pub fn trim(&mut self, limit: usize) {
+        if self.loading.is_none() && self.statuses.len() > limit {
+            self.statuses.truncate(limit);
+            self.end = false;
+        }
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
-        if self.statuses.is_empty() {
-            return match self.timeline {
-                TimelineType::Public => {
-                    Subscription::batch(vec![crate::subscriptions::public::timeline(
-                        self.mastodon.clone(),
-                    )])
-                }
-                TimelineType::Local => {
-                    Subscription::batch(vec![crate::subscriptions::public::local_timeline(
-                        self.mastodon.clone(),
-                    )])
-                }
-                TimelineType::Remote => {
-                    Subscription::batch(vec![crate::subscriptions::public::remote_timeline(
-                        self.mastodon.clone(),
-                    )])
-                }
-            };
+        match &self.loading {
+            Some(pagination) => Subscription::batch(vec![crate::subscriptions::public::timeline(
+                self.mastodon.clone(),
+                self.timeline.clone(),
+                pagination.clone(),
+            )]),
+            None => Subscription::none(),
         }
+    }
 
-        Subscription::none()
+    /// Wraps `message` for the app, which keeps a page for each kind of timeline.
+    fn app_message(&self, message: Message) -> app::Message {
+        match self.timeline {
+            TimelineType::Local => app::Message::Local(message),
+            TimelineType::Remote => app::Message::Federated(message),
+            TimelineType::Hashtag(_) => app::Message::Hashtag(message),
+        }
     }
 }
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length, Subscription},
+    iced_widget::scrollable::{Direction, Scrollbar},
+    widget, Apply, Element,
+};
+use mastodon_async::Mastodon;
+
+use crate::{
+    api::{self, ScheduledStatus},
+    app, fl,
+    storage::{self, Draft},
+};
+
+use super::MastodonPage;
+
+/// How times are typed and shown, in local time.
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How far ahead the server takes scheduled times, in minutes.
+const MIN_AHEAD: i64 = 5;
+
+/// Statuses the server posts later, and drafts kept on this device.
+#[derive(Debug, Clone)]
+pub struct Scheduled {
+    pub mastodon: Mastodon,
+    statuses: Vec<ScheduledStatus>,
+    drafts: Vec<Draft>,
+    /// Times typed to reschedule statuses, by status id.
+    times: HashMap<String, String>,
+    loading: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SetClient(Mastodon),
+    Refresh,
+    Loaded(Vec<ScheduledStatus>),
+    LoadFailed(String),
+    /// A status was scheduled from the composer.
+    Add(ScheduledStatus),
+    EditTime(String, String),
+    Reschedule(String),
+    Rescheduled(ScheduledStatus),
+    Cancel(String),
+    Cancelled(String),
+    RestoreDrafts(Vec<Draft>),
+    SaveDraft(Draft),
+    /// Opens a draft in the composer by the time it was saved, taking it off the list
+    /// until the composer is closed without sending it.
+    OpenDraft(DateTime<Utc>),
+    DeleteDraft(DateTime<Utc>),
+    /// Forgets the drafts, used when logging out.
+    Clear,
+}
+
+impl MastodonPage for Scheduled {
+    fn is_authenticated(&self) -> bool {
+        !self.mastodon.data.token.is_empty()
+    }
+}
+
+impl Scheduled {
+    pub fn new(mastodon: Mastodon) -> Self {
+        Self {
+            mastodon,
+            statuses: vec![],
+            drafts: vec![],
+            times: HashMap::new(),
+            loading: true,
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let refresh =
+            super::refresh_header(self.loading, (!self.loading).then_some(Message::Refresh));
+
+        let statuses: Element<_> = if self.statuses.is_empty() && !self.loading {
+            super::empty(fl!("no-scheduled-posts"))
+        } else {
+            widget::settings::section()
+                .extend(self.statuses.iter().map(|status| self.scheduled(status)))
+                .into()
+        };
+
+        let drafts: Element<_> = if self.drafts.is_empty() {
+            super::empty(fl!("no-drafts"))
+        } else {
+            widget::settings::section()
+                .extend(self.drafts.iter().map(draft))
+                .into()
+        };
+
+        widget::column()
+            .push(refresh)
+            .push(widget::text::heading(fl!("scheduled-posts")))
+            .push(statuses)
+            .push(widget::text::heading(fl!("drafts")))
+            .push(drafts)
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .direction(Direction::Vertical(
+                Scrollbar::default().spacing(spacing.space_xxs),
+            ))
+            .apply(widget::container)
+            .max_width(700)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let mut tasks = vec![];
+        match message {
+            Message::SetClient(mastodon) => {
+                self.mastodon = mastodon;
+                self.loading = true;
+            }
+            Message::Refresh => self.loading = true,
+            Message::Loaded(statuses) => {
+                self.loading = false;
+                self.times.clear();
+                self.statuses = statuses;
+                self.sort();
+            }
+            Message::LoadFailed(message) => {
+                self.loading = false;
+                tasks.push(cosmic::task::message(app::Message::Error(
+                    message,
+                    Some(Box::new(app::Message::Scheduled(Message::Refresh))),
+                )));
+            }
+            Message::Add(status) => {
+                self.statuses.push(status);
+                self.sort();
+            }
+            Message::EditTime(id, time) => {
+                self.times.insert(id, time);
+            }
+            Message::Reschedule(id) => {
+                let Some(time) = self.times.get(&id) else {
+                    return Task::none();
+                };
+                let Some(at) = parse_time(time) else {
+                    return cosmic::task::message(app::Message::Error(
+                        fl!("error-schedule-time"),
+                        None,
+                    ));
+                };
+                if too_soon(at) {
+                    return cosmic::task::message(app::Message::Error(
+                        fl!("error-schedule-soon"),
+                        None,
+                    ));
+                }
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::reschedule_status(&mastodon, &id, at).await {
+                        Ok(status) => app::Message::Scheduled(Message::Rescheduled(status)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Scheduled(Message::Reschedule(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::Rescheduled(status) => {
+                self.times.remove(&status.id);
+                self.statuses.retain(|scheduled| scheduled.id != status.id);
+                self.statuses.push(status);
+                self.sort();
+            }
+            Message::Cancel(id) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::cancel_scheduled_status(&mastodon, &id).await {
+                        Ok(()) => app::Message::Scheduled(Message::Cancelled(id)),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Scheduled(Message::Cancel(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::Cancelled(id) => {
+                self.times.remove(&id);
+                self.statuses.retain(|status| status.id != id);
+            }
+            Message::RestoreDrafts(restored) => {
+                // Drafts saved before the stored ones were loaded go after them.
+                let saved = std::mem::replace(&mut self.drafts, restored);
+                if !saved.is_empty() {
+                    self.drafts.extend(saved);
+                    tasks.push(self.drafts_changed());
+                }
+            }
+            Message::SaveDraft(draft) => {
+                self.drafts.push(draft);
+                tasks.push(self.drafts_changed());
+            }
+            Message::OpenDraft(saved_at) => {
+                let Some(index) = self.drafts.iter().position(|d| d.saved_at == saved_at) else {
+                    return Task::none();
+                };
+                let draft = self.drafts.remove(index);
+                tasks.push(cosmic::task::message(app::Message::OpenDraft(draft)));
+                tasks.push(self.drafts_changed());
+            }
+            Message::DeleteDraft(saved_at) => {
+                self.drafts.retain(|draft| draft.saved_at != saved_at);
+                tasks.push(self.drafts_changed());
+            }
+            Message::Clear => {
+                self.statuses.clear();
+                self.drafts.clear();
+                self.times.clear();
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.loading && self.is_authenticated() {
+            crate::subscriptions::scheduled::scheduled(self.mastodon.clone())
+        } else {
+            Subscription::none()
+        }
+    }
+
+    /// Keeps the statuses that are posted first on top.
+    fn sort(&mut self) {
+        self.statuses.sort_by_key(|status| status.scheduled_at);
+    }
+
+    fn drafts_changed(&self) -> Task<app::Message> {
+        let drafts = self.drafts.clone();
+        cosmic::task::future(async move {
+            if let Err(err) = storage::save_drafts(drafts).await {
+                tracing::error!("Failed to save drafts: {}", err);
+            }
+            app::Message::None
+        })
+    }
+
+    fn scheduled<'a>(&'a self, status: &'a ScheduledStatus) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+        let id = &status.id;
+        let time = self
+            .times
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| format_time(&status.scheduled_at));
+
+        let actions = widget::row()
+            .push(
+                widget::text_input(TIME_FORMAT, time)
+                    .on_input(|time| Message::EditTime(id.clone(), time))
+                    .on_submit(Message::Reschedule(id.clone()))
+                    .width(160),
+            )
+            .push(
+                widget::button::standard(fl!("reschedule")).on_press_maybe(
+                    self.times
+                        .contains_key(id)
+                        .then(|| Message::Reschedule(id.clone())),
+                ),
+            )
+            .push(widget::horizontal_space())
+            .push(widget::button::destructive(fl!("cancel")).on_press(Message::Cancel(id.clone())))
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs);
+
+        let content = widget::column()
+            .push(widget::text::caption(fl!(
+                "scheduled-for",
+                time = format_time(&status.scheduled_at)
+            )))
+            .push_maybe(
+                status
+                    .params
+                    .spoiler_text
+                    .as_deref()
+                    .filter(|spoiler| !spoiler.is_empty())
+                    .map(widget::text::heading),
+            )
+            .push(widget::text(&status.params.text))
+            .push(actions)
+            .spacing(spacing.space_xs);
+
+        widget::settings::flex_item_row(vec![content.into()])
+            .padding(spacing.space_xs)
+            .into()
+    }
+}
+
+fn draft(draft: &Draft) -> Element<Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+
+    let actions = widget::row()
+        .push(widget::horizontal_space())
+        .push(
+            widget::button::destructive(fl!("delete"))
+                .on_press(Message::DeleteDraft(draft.saved_at)),
+        )
+        .push(widget::button::suggested(fl!("open")).on_press(Message::OpenDraft(draft.saved_at)))
+        .spacing(spacing.space_xs);
+
+    let content = widget::column()
+        .push(widget::text::caption(fl!(
+            "draft-saved",
+            time = format_time(&draft.saved_at)
+        )))
+        .push_maybe(
+            draft
+                .spoiler_text
+                .as_deref()
+                .filter(|spoiler| !spoiler.is_empty())
+                .map(widget::text::heading),
+        )
+        .push(widget::text(&draft.text))
+        .push(actions)
+        .spacing(spacing.space_xs);
+
+    widget::settings::flex_item_row(vec![content.into()])
+        .padding(spacing.space_xs)
+        .into()
+}
+
+/// Reads a local time typed in `TIME_FORMAT`.
+pub fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    let time = NaiveDateTime::parse_from_str(time.trim(), TIME_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Whether the server would turn down `at` for being too close to now.
+pub fn too_soon(at: DateTime<Utc>) -> bool {
+    at < Utc::now() + chrono::Duration::minutes(MIN_AHEAD)
+}
+
+pub fn format_time(time: &DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format(TIME_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_times_are_read_as_local_time() {
+        let at = parse_time(" 2030-01-31 18:30 ").unwrap();
+        let local = at.with_timezone(&Local);
+        assert_eq!(local.format(TIME_FORMAT).to_string(), "2030-01-31 18:30");
+        assert_eq!(format_time(&at), "2030-01-31 18:30");
+    }
+
+    #[test]
+    fn malformed_times_are_rejected() {
+        assert_eq!(parse_time(""), None);
+        assert_eq!(parse_time("2030-01-31"), None);
+        assert_eq!(parse_time("18:30"), None);
+        assert_eq!(parse_time("2030-02-30 18:30"), None);
+    }
+
+    #[test]
+    fn times_need_to_be_far_enough_ahead() {
+        let now = Utc::now();
+        assert!(too_soon(now - chrono::Duration::hours(1)));
+        assert!(too_soon(now));
+        assert!(too_soon(now + chrono::Duration::minutes(MIN_AHEAD - 1)));
+        assert!(!too_soon(now + chrono::Duration::minutes(MIN_AHEAD + 1)));
+    }
+}
@@ -0,0 +1,172 @@
+use cosmic::{
+    app::command::Task,
+    iced::{Length, Subscription},
+    iced_widget::scrollable::{Direction, Scrollbar},
+    widget, Apply, Element,
+};
+use mastodon_async::prelude::{Account, Mastodon};
+
+use crate::{
+    api, app, fl,
+    utils::{self, Cache},
+    widgets,
+};
+
+use super::MastodonPage;
+
+/// Accounts the server suggests following, from the people the user interacts with
+/// and what is popular on the instance.
+#[derive(Debug, Clone)]
+pub struct Suggestions {
+    pub mastodon: Mastodon,
+    accounts: Vec<Account>,
+    loading: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SetClient(Mastodon),
+    Refresh,
+    Loaded(Vec<Account>),
+    LoadFailed(String),
+    Account(widgets::account::Message),
+    Follow(String),
+    Dismiss(String),
+    /// Drops a suggestion once it was followed or dismissed.
+    Remove(String),
+}
+
+impl MastodonPage for Suggestions {
+    fn is_authenticated(&self) -> bool {
+        !self.mastodon.data.token.is_empty()
+    }
+}
+
+impl Suggestions {
+    pub fn new(mastodon: Mastodon) -> Self {
+        Self {
+            mastodon,
+            accounts: vec![],
+            loading: true,
+        }
+    }
+
+    pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let refresh =
+            super::refresh_header(self.loading, (!self.loading).then_some(Message::Refresh));
+
+        let suggestions: Element<_> = if self.accounts.is_empty() && !self.loading {
+            super::empty(fl!("no-suggestions"))
+        } else {
+            widget::column()
+                .extend(
+                    self.accounts
+                        .iter()
+                        .map(|account| suggestion(account, cache)),
+                )
+                .spacing(spacing.space_m)
+                .into()
+        };
+
+        widget::column()
+            .push(refresh)
+            .push(suggestions)
+            .spacing(spacing.space_xs)
+            .apply(widget::scrollable)
+            .direction(Direction::Vertical(
+                Scrollbar::default().spacing(spacing.space_xxs),
+            ))
+            .apply(widget::container)
+            .max_width(700)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        let mut tasks = vec![];
+        match message {
+            Message::SetClient(mastodon) => {
+                self.mastodon = mastodon;
+                self.loading = self.accounts.is_empty();
+            }
+            Message::Refresh => self.loading = true,
+            Message::Loaded(accounts) => {
+                self.loading = false;
+                tasks.push(cosmic::task::message(app::Message::Fetch(
+                    accounts
+                        .iter()
+                        .flat_map(utils::extract_account_images)
+                        .collect(),
+                )));
+                self.accounts = accounts;
+            }
+            Message::LoadFailed(message) => {
+                self.loading = false;
+                tasks.push(cosmic::task::message(app::Message::Error(
+                    message,
+                    Some(Box::new(app::Message::Suggestions(Message::Refresh))),
+                )));
+            }
+            Message::Account(message) => tasks.push(widgets::account::update(message)),
+            Message::Follow(id) => {
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    match api::follow(&mastodon, &id).await {
+                        Ok(()) => app::Message::Followed(id),
+                        Err(err) => {
+                            tracing::error!("{err}");
+                            app::Message::Error(
+                                err.user_message(),
+                                Some(Box::new(app::Message::Suggestions(Message::Follow(id)))),
+                            )
+                        }
+                    }
+                }));
+            }
+            Message::Dismiss(id) => {
+                // The suggestion goes right away, a failure only means it may come back.
+                self.accounts.retain(|account| account.id.to_string() != id);
+                let mastodon = self.mastodon.clone();
+                tasks.push(cosmic::task::future(async move {
+                    if let Err(err) = api::dismiss_suggestion(&mastodon, &id).await {
+                        tracing::error!("Failed to dismiss suggestion: {}", err);
+                    }
+                    app::Message::None
+                }));
+            }
+            Message::Remove(id) => self.accounts.retain(|account| account.id.to_string() != id),
+        }
+        Task::batch(tasks)
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.iter()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.loading && self.is_authenticated() {
+            crate::subscriptions::suggestions::suggestions(self.mastodon.clone())
+        } else {
+            Subscription::none()
+        }
+    }
+}
+
+fn suggestion<'a>(account: &'a Account, cache: &'a Cache) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let id = account.id.to_string();
+
+    let actions = widget::row()
+        .push(widget::horizontal_space())
+        .push(widget::button::standard(fl!("dismiss")).on_press(Message::Dismiss(id.clone())))
+        .push(widget::button::suggested(fl!("follow")).on_press(Message::Follow(id)))
+        .spacing(spacing.space_xs);
+
+    widget::column()
+        .push(widgets::account(account, cache).map(Message::Account))
+        .push(actions)
+        .spacing(spacing.space_xs)
+        .into()
+}
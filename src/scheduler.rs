@@ -0,0 +1,259 @@
+//! Paces requests to each server by the rate limit it reports in the `X-RateLimit-*`
+//! headers, so bursts slow down before the limit is reached instead of failing.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{header::HeaderMap, Client, Request, Response, StatusCode};
+use tokio::{sync::watch, time::Instant};
+
+use crate::error::Error;
+
+/// Share of the limit left below which requests are spread out until the limit resets.
+const THRESHOLD: f64 = 0.2;
+/// Seconds requests wait after a rate limited response that doesn't say when it resets.
+const BACKOFF: i64 = 60;
+
+static LIMITS: LazyLock<Mutex<HashMap<String, Limit>>> = LazyLock::new(Default::default);
+/// How the most limited server holds requests back.
+static LIMITED: LazyLock<watch::Sender<Option<Limited>>> = LazyLock::new(|| watch::channel(None).0);
+
+/// How requests to a server are held back, and until when.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limited {
+    /// Requests are spread out as the limit runs low.
+    Throttled(DateTime<Utc>),
+    /// The server rejected a request as rate limited, nothing is sent before then.
+    Blocked(DateTime<Utc>),
+}
+
+impl Limited {
+    pub fn until(self) -> DateTime<Utc> {
+        match self {
+            Limited::Throttled(until) | Limited::Blocked(until) => until,
+        }
+    }
+}
+
+/// The last rate limit a server reported.
+#[derive(Debug, Default)]
+struct Limit {
+    limit: u32,
+    remaining: u32,
+    reset: Option<DateTime<Utc>>,
+    /// Set by a rate limited response, nothing is sent before it.
+    blocked_until: Option<DateTime<Utc>>,
+    /// When the next throttled request may start.
+    next: Option<Instant>,
+}
+
+impl Limit {
+    /// How long a request has to wait, counting it against the remaining requests.
+    fn delay(&mut self) -> Option<Duration> {
+        let now = Utc::now();
+        if let Some(blocked_until) = self.blocked_until {
+            if blocked_until > now {
+                return (blocked_until - now).to_std().ok();
+            }
+            self.blocked_until = None;
+        }
+
+        let reset = self.reset.filter(|reset| *reset > now)?;
+        let remaining = self.remaining;
+        self.remaining = remaining.saturating_sub(1);
+        if !self.is_throttled(now) {
+            return None;
+        }
+
+        // Spread what is left of the limit evenly over the time until it resets.
+        let interval = (reset - now).to_std().ok()? / remaining.max(1);
+        let now = Instant::now();
+        let start = self.next.map_or(now, |next| next.max(now));
+        self.next = Some(start + interval);
+        Some(start - now).filter(|delay| !delay.is_zero())
+    }
+
+    fn is_throttled(&self, now: DateTime<Utc>) -> bool {
+        self.reset.is_some_and(|reset| reset > now)
+            && f64::from(self.remaining) < f64::from(self.limit) * THRESHOLD
+    }
+
+    /// Whether requests are slowed down or held back, and until when.
+    fn limited(&self, now: DateTime<Utc>) -> Option<Limited> {
+        match self.blocked_until {
+            Some(blocked_until) if blocked_until > now => Some(Limited::Blocked(blocked_until)),
+            _ => self
+                .reset
+                .filter(|_| self.is_throttled(now))
+                .map(Limited::Throttled),
+        }
+    }
+}
+
+/// Sends `request` once the rate limit of its server allows it, waiting for the limit
+/// to reset and sending it again if the server still rejects it as rate limited.
+pub async fn send(client: &Client, request: Request) -> Result<Response, Error> {
+    let host = request.url().host_str().unwrap_or_default().to_string();
+    let retry = request.try_clone();
+
+    wait(&host).await;
+    let response = client.execute(request).await?;
+    update(&host, response.headers());
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(response);
+    }
+
+    block(&host, reset(response.headers()));
+    match retry {
+        Some(retry) => {
+            wait(&host).await;
+            let response = client.execute(retry).await?;
+            update(&host, response.headers());
+            Ok(response)
+        }
+        None => Ok(response),
+    }
+}
+
+/// When the rate limit reported in `headers` resets.
+pub fn reset(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    let reset = headers.get("x-ratelimit-reset")?.to_str().ok()?;
+    DateTime::parse_from_rfc3339(reset)
+        .ok()
+        .map(|reset| reset.with_timezone(&Utc))
+}
+
+/// Follows how requests are slowed down, `None` while they aren't.
+pub fn subscribe() -> watch::Receiver<Option<Limited>> {
+    LIMITED.subscribe()
+}
+
+async fn wait(host: &str) {
+    let delay = {
+        let mut limits = LIMITS.lock().unwrap_or_else(PoisonError::into_inner);
+        limits.entry(host.to_string()).or_default().delay()
+    };
+    if let Some(delay) = delay {
+        tracing::debug!("delaying request to {} by {:?}", host, delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn update(host: &str, headers: &HeaderMap) {
+    let header = |name: &str| -> Option<u32> { headers.get(name)?.to_str().ok()?.parse().ok() };
+    let (Some(limit), Some(remaining)) =
+        (header("x-ratelimit-limit"), header("x-ratelimit-remaining"))
+    else {
+        return;
+    };
+
+    let mut limits = LIMITS.lock().unwrap_or_else(PoisonError::into_inner);
+    let entry = limits.entry(host.to_string()).or_default();
+    entry.limit = limit;
+    entry.remaining = remaining;
+    entry.reset = reset(headers);
+    publish(&limits);
+}
+
+fn block(host: &str, reset: Option<DateTime<Utc>>) {
+    let until = reset.unwrap_or_else(|| Utc::now() + chrono::Duration::seconds(BACKOFF));
+    tracing::warn!("rate limited by {} until {}", host, until);
+
+    let mut limits = LIMITS.lock().unwrap_or_else(PoisonError::into_inner);
+    limits.entry(host.to_string()).or_default().blocked_until = Some(until);
+    publish(&limits);
+}
+
+fn publish(limits: &HashMap<String, Limit>) {
+    let now = Utc::now();
+    // A blocked server is reported over throttled ones, as nothing reaches it.
+    let until = limits
+        .values()
+        .filter_map(|limit| limit.limited(now))
+        .max_by_key(|limited| (matches!(limited, Limited::Blocked(_)), limited.until()));
+    LIMITED.send_if_modified(|limited| {
+        let modified = *limited != until;
+        *limited = until;
+        modified
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(limit: u32, remaining: u32, reset_in: i64) -> Limit {
+        Limit {
+            limit,
+            remaining,
+            reset: Some(Utc::now() + chrono::Duration::seconds(reset_in)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unknown_limits_dont_delay() {
+        assert_eq!(Limit::default().delay(), None);
+    }
+
+    #[test]
+    fn requests_above_the_threshold_go_right_away() {
+        let mut limit = limit(300, 299, 300);
+        assert_eq!(limit.delay(), None);
+        assert_eq!(limit.remaining, 298);
+    }
+
+    #[test]
+    fn requests_below_the_threshold_are_spread_out() {
+        // 10 requests left over 100 seconds leaves 10 seconds between requests.
+        let mut limit = limit(300, 10, 100);
+        assert_eq!(limit.delay(), None);
+        let second = limit.delay().unwrap();
+        assert!(second > Duration::from_secs(9) && second <= Duration::from_secs(10));
+        let third = limit.delay().unwrap();
+        assert!(third > second);
+    }
+
+    #[test]
+    fn limits_that_reset_dont_delay() {
+        let mut limit = limit(300, 0, -1);
+        assert_eq!(limit.delay(), None);
+    }
+
+    #[test]
+    fn blocked_requests_wait_for_the_reset() {
+        let mut limit = Limit {
+            blocked_until: Some(Utc::now() + chrono::Duration::seconds(30)),
+            ..Default::default()
+        };
+        let delay = limit.delay().unwrap();
+        assert!(delay > Duration::from_secs(29) && delay <= Duration::from_secs(30));
+
+        limit.blocked_until = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert_eq!(limit.delay(), None);
+        assert_eq!(limit.blocked_until, None);
+    }
+
+    #[test]
+    fn blocked_servers_are_reported_over_throttled_ones() {
+        let now = Utc::now();
+        let blocked = Limit {
+            blocked_until: Some(now + chrono::Duration::seconds(30)),
+            ..Default::default()
+        };
+        assert_eq!(
+            blocked.limited(now),
+            Some(Limited::Blocked(now + chrono::Duration::seconds(30)))
+        );
+        let throttled = limit(300, 10, 100);
+        assert!(matches!(
+            throttled.limited(now),
+            Some(Limited::Throttled(_))
+        ));
+        assert_eq!(limit(300, 299, 100).limited(now), None);
+    }
+}
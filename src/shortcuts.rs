@@ -0,0 +1,256 @@
+//! Keyboard navigation through the statuses of a page, with key sequences like `g h`
+//! configured in the settings.
+
+use cosmic::{
+    iced::keyboard::{key::Named, Key, Modifiers},
+    widget::menu::key_bind::{KeyBind, Modifier},
+};
+
+use crate::{config::KeyBindings, fl};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Selects the status below the selected one.
+    Next,
+    Previous,
+    Favourite,
+    Boost,
+    Reply,
+    Compose,
+    /// Opens the thread of the selected status.
+    Open,
+    Home,
+    Notifications,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::Next,
+        Action::Previous,
+        Action::Favourite,
+        Action::Boost,
+        Action::Reply,
+        Action::Compose,
+        Action::Open,
+        Action::Home,
+        Action::Notifications,
+    ];
+
+    pub fn label(self) -> String {
+        match self {
+            Action::Next => fl!("shortcut-next"),
+            Action::Previous => fl!("shortcut-previous"),
+            Action::Favourite => fl!("shortcut-favourite"),
+            Action::Boost => fl!("shortcut-boost"),
+            Action::Reply => fl!("shortcut-reply"),
+            Action::Compose => fl!("shortcut-compose"),
+            Action::Open => fl!("shortcut-open"),
+            Action::Home => fl!("shortcut-home"),
+            Action::Notifications => fl!("shortcut-notifications"),
+        }
+    }
+}
+
+/// Matches pressed keys against the configured sequences.
+#[derive(Debug, Default)]
+pub struct Shortcuts {
+    bindings: Vec<(Vec<KeyBind>, Action)>,
+    /// The keys pressed so far of an unfinished sequence.
+    pressed: Vec<(Modifiers, Key)>,
+}
+
+impl Shortcuts {
+    /// Bindings that can't be parsed are left out.
+    pub fn new(key_bindings: &KeyBindings) -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .filter_map(|action| {
+                let keys = key_bindings.get(action);
+                let sequence = parse(keys);
+                if sequence.is_none() {
+                    tracing::warn!("ignoring invalid key binding {:?} for {:?}", keys, action);
+                }
+                sequence.map(|sequence| (sequence, action))
+            })
+            .collect();
+        Self {
+            bindings,
+            pressed: vec![],
+        }
+    }
+
+    /// Adds a pressed key, returning the action of the sequence it completes.
+    pub fn press(&mut self, modifiers: Modifiers, key: Key) -> Option<Action> {
+        // Modifiers are only held down while pressing the key they modify.
+        if let Key::Named(Named::Shift | Named::Control | Named::Alt | Named::Super) = key {
+            return None;
+        }
+        self.pressed.push((modifiers, key));
+
+        loop {
+            let starts = |sequence: &[KeyBind]| {
+                sequence.len() >= self.pressed.len()
+                    && sequence
+                        .iter()
+                        .zip(&self.pressed)
+                        .all(|(bind, (modifiers, key))| bind.matches(*modifiers, key))
+            };
+            let complete = self
+                .bindings
+                .iter()
+                .find(|(sequence, _)| sequence.len() == self.pressed.len() && starts(sequence));
+            if let Some((_, action)) = complete {
+                let action = *action;
+                self.pressed.clear();
+                return Some(action);
+            }
+            if self.bindings.iter().any(|(sequence, _)| starts(sequence)) {
+                return None;
+            }
+            // No sequence goes on like this, but the last key may start another one.
+            if self.pressed.len() <= 1 {
+                self.pressed.clear();
+                return None;
+            }
+            self.pressed.drain(..self.pressed.len() - 1);
+        }
+    }
+}
+
+/// Parses space-separated keys, each with optional modifiers like `Ctrl+Enter`.
+pub fn parse(sequence: &str) -> Option<Vec<KeyBind>> {
+    let sequence: Option<Vec<KeyBind>> = sequence.split_whitespace().map(parse_key).collect();
+    sequence.filter(|sequence| !sequence.is_empty())
+}
+
+fn parse_key(keys: &str) -> Option<KeyBind> {
+    let mut parts: Vec<&str> = keys.split('+').collect();
+    let key = parts.pop()?;
+    let modifiers = parts
+        .into_iter()
+        .map(|modifier| match modifier.to_lowercase().as_str() {
+            "ctrl" => Some(Modifier::Ctrl),
+            "alt" => Some(Modifier::Alt),
+            "shift" => Some(Modifier::Shift),
+            "super" => Some(Modifier::Super),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let key = match key {
+        "Enter" => Key::Named(Named::Enter),
+        "Space" => Key::Named(Named::Space),
+        "Tab" => Key::Named(Named::Tab),
+        "Up" => Key::Named(Named::ArrowUp),
+        "Down" => Key::Named(Named::ArrowDown),
+        "Left" => Key::Named(Named::ArrowLeft),
+        "Right" => Key::Named(Named::ArrowRight),
+        "PageUp" => Key::Named(Named::PageUp),
+        "PageDown" => Key::Named(Named::PageDown),
+        key if key.chars().count() == 1 => Key::Character(key.to_lowercase().into()),
+        _ => return None,
+    };
+    Some(KeyBind { modifiers, key })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Key {
+        Key::Character(key.into())
+    }
+
+    fn press(shortcuts: &mut Shortcuts, keys: &str) -> Option<Action> {
+        keys.split_whitespace()
+            .map(|k| shortcuts.press(Modifiers::empty(), key(k)))
+            .last()
+            .flatten()
+    }
+
+    #[test]
+    fn parses_sequences() {
+        let sequence = parse("g h").unwrap();
+        assert_eq!(sequence.len(), 2);
+        assert_eq!(sequence[0].key, key("g"));
+        assert_eq!(sequence[1].key, key("h"));
+        assert!(sequence.iter().all(|bind| bind.modifiers.is_empty()));
+
+        let sequence = parse("Ctrl+Shift+Enter").unwrap();
+        assert_eq!(sequence.len(), 1);
+        assert_eq!(sequence[0].modifiers, vec![Modifier::Ctrl, Modifier::Shift]);
+        assert_eq!(sequence[0].key, Key::Named(Named::Enter));
+
+        assert_eq!(parse("  G ").unwrap()[0].key, key("g"));
+    }
+
+    #[test]
+    fn rejects_invalid_sequences() {
+        assert!(parse("").is_none());
+        assert!(parse("   ").is_none());
+        assert!(parse("Hyper+j").is_none());
+        assert!(parse("g Escape").is_none());
+        assert!(parse("Ctrl+").is_none());
+    }
+
+    #[test]
+    fn matches_single_keys() {
+        let mut shortcuts = Shortcuts::new(&KeyBindings::default());
+        assert_eq!(press(&mut shortcuts, "j"), Some(Action::Next));
+        assert_eq!(press(&mut shortcuts, "k"), Some(Action::Previous));
+        assert_eq!(
+            shortcuts.press(Modifiers::empty(), Key::Named(Named::Enter)),
+            Some(Action::Open)
+        );
+        assert_eq!(press(&mut shortcuts, "x"), None);
+        assert_eq!(shortcuts.press(Modifiers::CTRL, key("j")), None);
+    }
+
+    #[test]
+    fn matches_sequences() {
+        let mut shortcuts = Shortcuts::new(&KeyBindings::default());
+        assert_eq!(shortcuts.press(Modifiers::empty(), key("g")), None);
+        assert_eq!(
+            shortcuts.press(Modifiers::empty(), key("h")),
+            Some(Action::Home)
+        );
+        assert_eq!(press(&mut shortcuts, "g n"), Some(Action::Notifications));
+        // The sequence is reset once it completed.
+        assert_eq!(press(&mut shortcuts, "h"), None);
+    }
+
+    #[test]
+    fn ignores_modifier_keys_within_sequences() {
+        let mut shortcuts = Shortcuts::new(&KeyBindings::default());
+        assert_eq!(shortcuts.press(Modifiers::empty(), key("g")), None);
+        assert_eq!(
+            shortcuts.press(Modifiers::SHIFT, Key::Named(Named::Shift)),
+            None
+        );
+        assert_eq!(
+            shortcuts.press(Modifiers::empty(), key("h")),
+            Some(Action::Home)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_last_key() {
+        let mut shortcuts = Shortcuts::new(&KeyBindings::default());
+        // `g j` isn't a sequence, but `j` is bound on its own.
+        assert_eq!(press(&mut shortcuts, "g j"), Some(Action::Next));
+        // `g g` isn't a sequence either, the second `g` starts `g h` again.
+        assert_eq!(press(&mut shortcuts, "g g h"), Some(Action::Home));
+        // Unbound keys drop the unfinished sequence.
+        assert_eq!(press(&mut shortcuts, "g x h"), None);
+    }
+
+    #[test]
+    fn leaves_out_invalid_bindings() {
+        let key_bindings = KeyBindings {
+            next: "Hyper+j".into(),
+            ..KeyBindings::default()
+        };
+        let mut shortcuts = Shortcuts::new(&key_bindings);
+        assert_eq!(press(&mut shortcuts, "j"), None);
+        assert_eq!(press(&mut shortcuts, "k"), Some(Action::Previous));
+    }
+}
@@ -0,0 +1,307 @@
+//! Persists timelines, accounts and images under the XDG cache directory, so the last
+//! known state can be shown while the network catches up. Queued actions and drafts go to
+//! the XDG data directory instead, as they can't be fetched again.
+
+use std::{path::PathBuf, time::SystemTime};
+
+use chrono::{DateTime, Utc};
+use cosmic::Application;
+use mastodon_async::{
+    prelude::{Account, Notification, Status, Visibility},
+    NewStatus,
+};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{api::PollSource, app::AppModel, error::Error, outbox::Outbox};
+
+/// Statuses or notifications kept per timeline.
+pub const TIMELINE_LIMIT: usize = 100;
+/// Accounts kept on disk, the most recently seen ones.
+pub const ACCOUNT_LIMIT: usize = 500;
+/// Bytes of image data kept on disk before the least recently used images are evicted.
+const IMAGE_LIMIT: u64 = 200 * 1024 * 1024;
+/// Bytes of image data left on disk once images are evicted.
+const IMAGE_LOW_WATER: u64 = IMAGE_LIMIT / 4 * 3;
+
+const SNAPSHOT: &str = "snapshot.json";
+const IMAGES: &str = "images";
+const OUTBOX: &str = "outbox.json";
+const DRAFTS: &str = "drafts.json";
+
+/// The last known contents of every page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub home: Vec<Status>,
+    pub notifications: Vec<Notification>,
+    pub explore: Vec<Status>,
+    pub local: Vec<Status>,
+    pub federated: Vec<Status>,
+    pub accounts: Vec<Account>,
+}
+
+/// What the composer held when it was closed without posting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    pub text: String,
+    pub in_reply_to_id: Option<String>,
+    pub visibility: Option<Visibility>,
+    pub spoiler_text: Option<String>,
+    pub sensitive: Option<bool>,
+    /// Attachments that were uploaded for the post.
+    pub media_ids: Option<Vec<String>>,
+    /// The poll being written, if any.
+    pub poll: Option<PollSource>,
+    /// The time typed to schedule the post, as it was typed.
+    #[serde(default)]
+    pub schedule: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl Draft {
+    /// Keeps the composer state of `status`.
+    pub fn new(status: NewStatus, poll: Option<PollSource>, schedule: String) -> Self {
+        Self {
+            text: status.status.unwrap_or_default().trim_end().to_string(),
+            in_reply_to_id: status.in_reply_to_id,
+            visibility: status.visibility,
+            spoiler_text: status.spoiler_text,
+            sensitive: status.sensitive,
+            media_ids: status.media_ids,
+            poll,
+            schedule,
+            saved_at: Utc::now(),
+        }
+    }
+
+    /// Whether there's more to the draft than the mentions a reply starts with.
+    pub fn is_empty(&self) -> bool {
+        !self
+            .text
+            .split_whitespace()
+            .any(|word| !word.starts_with('@'))
+            && self.media_ids.as_deref().unwrap_or_default().is_empty()
+            && self.poll.is_none()
+    }
+
+    /// Opens the draft in the composer again.
+    pub fn new_status(&self) -> NewStatus {
+        NewStatus {
+            status: Some(self.text.clone()),
+            in_reply_to_id: self.in_reply_to_id.clone(),
+            visibility: self.visibility,
+            spoiler_text: self.spoiler_text.clone(),
+            sensitive: self.sensitive,
+            media_ids: self.media_ids.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(AppModel::APP_ID))
+}
+
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(AppModel::APP_ID))
+}
+
+/// Where the cached bytes of the image at `url` are kept.
+pub fn image_path(url: &Url) -> Option<PathBuf> {
+    let name = format!("{:016x}", fnv1a(url.as_str().as_bytes()));
+    cache_dir().map(|dir| dir.join(IMAGES).join(name))
+}
+
+/// 64-bit FNV-1a, which unlike the std hashers gives the same file names across
+/// releases and runs.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+async fn read_json<T: DeserializeOwned>(path: PathBuf) -> Result<T, Error> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn write_json<T: Serialize>(path: PathBuf, value: &T) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, serde_json::to_vec(value)?).await?;
+    Ok(())
+}
+
+pub async fn load_snapshot() -> Snapshot {
+    let Some(dir) = cache_dir() else {
+        return Snapshot::default();
+    };
+    match read_json(dir.join(SNAPSHOT)).await {
+        Ok(snapshot) => snapshot,
+        Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => Snapshot::default(),
+        Err(err) => {
+            tracing::warn!("failed to load cached timelines: {}", err);
+            Snapshot::default()
+        }
+    }
+}
+
+pub async fn save_snapshot(snapshot: Snapshot) -> Result<(), Error> {
+    match cache_dir() {
+        Some(dir) => write_json(dir.join(SNAPSHOT), &snapshot).await,
+        None => Ok(()),
+    }
+}
+
+pub async fn load_outbox() -> Outbox {
+    let Some(dir) = data_dir() else {
+        return Outbox::default();
+    };
+    match read_json(dir.join(OUTBOX)).await {
+        Ok(outbox) => outbox,
+        Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => Outbox::default(),
+        Err(err) => {
+            tracing::warn!("failed to load queued actions: {}", err);
+            Outbox::default()
+        }
+    }
+}
+
+pub async fn save_outbox(outbox: Outbox) -> Result<(), Error> {
+    match data_dir() {
+        Some(dir) => write_json(dir.join(OUTBOX), &outbox).await,
+        None => Ok(()),
+    }
+}
+
+pub async fn load_drafts() -> Vec<Draft> {
+    let Some(dir) = data_dir() else {
+        return vec![];
+    };
+    match read_json(dir.join(DRAFTS)).await {
+        Ok(drafts) => drafts,
+        Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(err) => {
+            tracing::warn!("failed to load drafts: {}", err);
+            vec![]
+        }
+    }
+}
+
+pub async fn save_drafts(drafts: Vec<Draft>) -> Result<(), Error> {
+    match data_dir() {
+        Some(dir) => write_json(dir.join(DRAFTS), &drafts).await,
+        None => Ok(()),
+    }
+}
+
+pub async fn load_image(url: &Url) -> Option<Vec<u8>> {
+    let path = image_path(url)?;
+    let bytes = tokio::fs::read(&path).await.ok()?;
+    // Reading doesn't update the modification time, which is what eviction sorts by.
+    let touched = tokio::task::spawn_blocking(move || {
+        std::fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+    })
+    .await;
+    match touched {
+        Ok(Ok(())) => (),
+        Ok(Err(err)) => tracing::warn!("failed to touch cached image: {}", err),
+        Err(err) => tracing::warn!("failed to touch cached image: {}", err),
+    }
+    Some(bytes)
+}
+
+pub async fn save_image(url: &Url, bytes: &[u8]) -> Result<(), Error> {
+    let Some(path) = image_path(url) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, bytes).await?;
+    Ok(())
+}
+
+/// Once the image cache is over [`IMAGE_LIMIT`], removes the least recently used images
+/// until it fits in [`IMAGE_LOW_WATER`].
+pub async fn evict_images() -> Result<(), Error> {
+    let Some(dir) = cache_dir().map(|dir| dir.join(IMAGES)) else {
+        return Ok(());
+    };
+    let mut read_dir = match tokio::fs::read_dir(&dir).await {
+        Ok(read_dir) => read_dir,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut images = vec![];
+    while let Some(entry) = read_dir.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        images.push((metadata.modified()?, metadata.len(), entry.path()));
+    }
+
+    let mut size: u64 = images.iter().map(|(_, len, _)| len).sum();
+    if size <= IMAGE_LIMIT {
+        return Ok(());
+    }
+    images.sort_by_key(|(modified, _, _)| *modified);
+    for (_, len, path) in images {
+        if size <= IMAGE_LOW_WATER {
+            break;
+        }
+        tokio::fs::remove_file(path).await?;
+        size -= len;
+    }
+    Ok(())
+}
+
+/// Removes everything, including queued actions and drafts, used when logging out.
+pub async fn clear() -> Result<(), Error> {
+    for file in [OUTBOX, DRAFTS] {
+        if let Some(path) = data_dir().map(|dir| dir.join(file)) {
+            match tokio::fs::remove_file(path).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => (),
+            }
+        }
+    }
+    match cache_dir() {
+        Some(dir) => match tokio::fs::remove_dir_all(dir).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        },
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn drafts_with_only_mentions_are_empty_unless_they_have_media() {
+        let draft = |text: &str, media_ids: Option<Vec<String>>| {
+            let status = NewStatus {
+                status: Some(text.to_string()),
+                media_ids,
+                ..Default::default()
+            };
+            Draft::new(status, None, String::new())
+        };
+        assert!(draft("@alice @bob ", None).is_empty());
+        assert!(draft("@alice", Some(vec![])).is_empty());
+        assert!(!draft("@alice hi", None).is_empty());
+        assert!(!draft("", Some(vec!["1".to_string()])).is_empty());
+    }
+}
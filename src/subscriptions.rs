@@ -1,21 +1,70 @@
+use std::{future::Future, hash::Hash};
+
 use crate::pages;
+use chrono::Utc;
 use cosmic::iced::{stream, Subscription};
 use futures_util::{SinkExt, TryStreamExt};
 use mastodon_async::entities::event::Event;
 use mastodon_async::Mastodon;
 
-use crate::app;
+use crate::{app, fl, scheduler};
 
+pub mod conversations;
+pub mod explore;
+pub mod follow_requests;
 pub mod home;
+pub mod moderation;
 pub mod notifications;
 pub mod public;
+pub mod scheduled;
+pub mod suggestions;
+
+/// Sends the message `future` resolves to once, and nothing else for as long as the
+/// subscription with `id` is kept. Pages keep it while they're loading.
+pub fn once<I, M>(id: I, future: impl Future<Output = M> + Send + 'static) -> Subscription<M>
+where
+    I: Hash + 'static,
+    M: Send + 'static,
+{
+    Subscription::run_with_id(
+        id,
+        stream::channel(1, move |mut output| async move {
+            let message = future.await;
+            if let Err(err) = output.send(message).await {
+                tracing::warn!("failed to send loaded page: {}", err);
+            }
+
+            std::future::pending().await
+        }),
+    )
+}
 
 pub fn stream_user_events(mastodon: Mastodon) -> Subscription<app::Message> {
     Subscription::run_with_id(
         "posts",
-        stream::channel(1, |output| async move {
-            let stream = mastodon.stream_user().await.unwrap();
-            stream
+        stream::channel(1, |mut output| async move {
+            let stream = match mastodon.stream_user().await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    tracing::error!("failed to connect to the user stream: {}", err);
+                    let message = fl!("error-streaming");
+                    if let Err(err) = output.send(app::Message::Error(message, None)).await {
+                        tracing::warn!("failed to send error: {}", err);
+                    }
+                    std::future::pending().await
+                }
+            };
+
+            // Statuses posted while the stream was down are only picked up by the timeline,
+            // refreshing it lets the home page detect the gap.
+            if let Err(err) = output
+                .send(app::Message::Home(pages::home::Message::Refresh))
+                .await
+            {
+                tracing::warn!("failed to send refresh: {}", err);
+            }
+
+            let result = stream
                 .try_for_each(|(event, _client)| {
                     let mut output = output.clone();
                     async move {
@@ -57,10 +106,41 @@ pub fn stream_user_events(mastodon: Mastodon) -> Subscription<app::Message> {
                         Ok(())
                     }
                 })
-                .await
-                .unwrap();
+                .await;
+            if let Err(err) = result {
+                tracing::error!("user stream closed: {}", err);
+            }
 
             std::future::pending().await
         }),
     )
 }
+
+/// Reports how requests are slowed down by rate limits, and when that ends.
+pub fn rate_limit() -> Subscription<app::Message> {
+    Subscription::run_with_id(
+        "rate-limit",
+        stream::channel(1, |mut output| async move {
+            let mut limited = scheduler::subscribe();
+            loop {
+                let current = *limited.borrow_and_update();
+                let current = current.filter(|current| current.until() > Utc::now());
+                if let Err(err) = output.send(app::Message::RateLimited(current)).await {
+                    tracing::warn!("failed to send rate limit: {}", err);
+                }
+
+                let until = current.map(scheduler::Limited::until);
+                let changed = match until.and_then(|until| (until - Utc::now()).to_std().ok()) {
+                    // The limit also ends without a new response clearing it.
+                    Some(wait) => tokio::time::timeout(wait, limited.changed())
+                        .await
+                        .unwrap_or(Ok(())),
+                    None => limited.changed().await,
+                };
+                if changed.is_err() {
+                    std::future::pending::<()>().await;
+                }
+            }
+        }),
+    )
+}
@@ -0,0 +1,20 @@
+use cosmic::iced::Subscription;
+use mastodon_async::Mastodon;
+
+use crate::{api, pages};
+
+pub fn conversations(
+    mastodon: Mastodon,
+    pagination: api::Pagination,
+) -> Subscription<pages::conversations::Message> {
+    let id = format!("conversations-{}-{:?}", mastodon.data.base, pagination);
+    super::once(id, async move {
+        match api::conversations(&mastodon, &pagination).await {
+            Ok(conversations) => pages::conversations::Message::Loaded(pagination, conversations),
+            Err(err) => {
+                tracing::warn!("failed to get conversations: {}", err);
+                pages::conversations::Message::LoadFailed(err.user_message())
+            }
+        }
+    })
+}
@@ -0,0 +1,29 @@
+use cosmic::iced::Subscription;
+use mastodon_async::Mastodon;
+
+use crate::{
+    api,
+    pages::{self, explore::Load},
+};
+
+pub fn trends(mastodon: Mastodon, load: Load) -> Subscription<pages::explore::Message> {
+    let id = format!("trends-{}-{:?}", mastodon.data.base, load);
+    super::once(id, async move {
+        let result = match load {
+            Load::Posts(offset) => api::trending_statuses(&mastodon, offset)
+                .await
+                .map(|statuses| pages::explore::Message::PostsLoaded(offset, statuses)),
+            Load::Hashtags => api::trending_tags(&mastodon)
+                .await
+                .map(pages::explore::Message::HashtagsLoaded),
+            Load::Links => api::trending_links(&mastodon)
+                .await
+                .map(pages::explore::Message::LinksLoaded),
+        };
+
+        result.unwrap_or_else(|err| {
+            tracing::warn!("failed to get {:?} trends: {}", load, err);
+            pages::explore::Message::LoadFailed(err.user_message())
+        })
+    })
+}
@@ -0,0 +1,22 @@
+use cosmic::iced::Subscription;
+use mastodon_async::Mastodon;
+
+use crate::{api, pages};
+
+pub fn pending(
+    mastodon: Mastodon,
+    pagination: api::Pagination,
+) -> Subscription<pages::follow_requests::Message> {
+    let id = format!("follow-requests-{}-{:?}", mastodon.data.base, pagination);
+    super::once(id, async move {
+        match api::follow_requests(&mastodon, &pagination).await {
+            Ok((accounts, next)) => {
+                pages::follow_requests::Message::Loaded(pagination, accounts, next)
+            }
+            Err(err) => {
+                tracing::warn!("failed to get follow requests: {}", err);
+                pages::follow_requests::Message::LoadFailed(err.user_message())
+            }
+        }
+    })
+}
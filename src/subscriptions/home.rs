@@ -1,36 +1,20 @@
-use cosmic::iced::{stream, Subscription};
-use futures_util::{SinkExt, StreamExt};
+use cosmic::iced::Subscription;
 use mastodon_async::Mastodon;
 
-use crate::pages;
+use crate::{api, pages};
 
-pub fn user_timeline(mastodon: Mastodon, skip: usize) -> Subscription<pages::home::Message> {
-    Subscription::run_with_id(
-        format!("timeline-{}-{}", skip, mastodon.data.base),
-        stream::channel(1, move |mut output| async move {
-            println!("{}", format!("timeline-{}-{}", skip, mastodon.data.base));
-
-            // First fetch the timeline
-            let mut stream = Box::pin(
-                mastodon
-                    .get_home_timeline()
-                    .await
-                    .unwrap()
-                    .items_iter()
-                    .skip(skip)
-                    .take(20),
-            );
-
-            while let Some(status) = stream.next().await {
-                if let Err(err) = output
-                    .send(pages::home::Message::AppendStatus(status.clone()))
-                    .await
-                {
-                    tracing::warn!("failed to send post: {}", err);
-                }
+pub fn user_timeline(
+    mastodon: Mastodon,
+    pagination: api::Pagination,
+) -> Subscription<pages::home::Message> {
+    let id = format!("timeline-{}-{:?}", mastodon.data.base, pagination);
+    super::once(id, async move {
+        match api::home_timeline(&mastodon, &pagination).await {
+            Ok(statuses) => pages::home::Message::Loaded(pagination, statuses),
+            Err(err) => {
+                tracing::warn!("failed to get home timeline: {}", err);
+                pages::home::Message::LoadFailed(err.user_message())
             }
-
-            std::future::pending().await
-        }),
-    )
+        }
+    })
 }
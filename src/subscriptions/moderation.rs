@@ -0,0 +1,23 @@
+use cosmic::iced::Subscription;
+use mastodon_async::Mastodon;
+
+use crate::{api, pages};
+
+pub fn queue(mastodon: Mastodon) -> Subscription<pages::moderation::Message> {
+    let id = format!("moderation-{}", mastodon.data.base);
+    super::once(id, async move {
+        let queue = match api::reports(&mastodon).await {
+            Ok(reports) => api::pending_accounts(&mastodon)
+                .await
+                .map(|accounts| (reports, accounts)),
+            Err(err) => Err(err),
+        };
+        match queue {
+            Ok((reports, accounts)) => pages::moderation::Message::Loaded(reports, accounts),
+            Err(err) => {
+                tracing::warn!("failed to get moderation queue: {}", err);
+                pages::moderation::Message::LoadFailed(err.user_message())
+            }
+        }
+    })
+}
@@ -1,35 +1,25 @@
-use cosmic::iced::{stream, Subscription};
-use futures_util::{SinkExt, StreamExt};
+use cosmic::iced::Subscription;
 use mastodon_async::Mastodon;
 
-use crate::pages;
+use crate::{api, pages};
 
-pub fn timeline(mastodon: Mastodon) -> Subscription<pages::notifications::Message> {
-    Subscription::run_with_id(
-        format!("notifications-{}", mastodon.data.base),
-        stream::channel(1, |mut output| async move {
-            println!("{}", format!("notifications-{}", mastodon.data.base));
-            let mut stream = Box::pin(
-                mastodon
-                    .notifications()
-                    .await
-                    .unwrap()
-                    .items_iter()
-                    .take(100),
-            );
-
-            while let Some(notification) = stream.next().await {
-                if let Err(err) = output
-                    .send(pages::notifications::Message::AppendNotification(
-                        notification.clone(),
-                    ))
-                    .await
-                {
-                    tracing::warn!("failed to send post: {}", err);
-                }
+/// A page of notifications of `types`, or of every type while it's empty.
+pub fn timeline(
+    mastodon: Mastodon,
+    pagination: api::Pagination,
+    types: Vec<&'static str>,
+) -> Subscription<pages::notifications::Message> {
+    let id = format!(
+        "notifications-{}-{:?}-{:?}",
+        mastodon.data.base, pagination, types
+    );
+    super::once(id, async move {
+        match api::notifications(&mastodon, &pagination, &types).await {
+            Ok(notifications) => pages::notifications::Message::Loaded(pagination, notifications),
+            Err(err) => {
+                tracing::warn!("failed to get notifications: {}", err);
+                pages::notifications::Message::LoadFailed(err.user_message())
             }
-
-            std::future::pending().await
-        }),
-    )
+        }
+    })
 }
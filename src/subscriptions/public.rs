@@ -1,80 +1,32 @@
-use cosmic::iced::{stream, Subscription};
-use futures_util::SinkExt;
+use cosmic::iced::Subscription;
 use mastodon_async::Mastodon;
 
-use crate::pages;
+use crate::{api, pages, pages::public::TimelineType};
 
-pub fn timeline(mastodon: Mastodon) -> Subscription<pages::public::Message> {
-    Subscription::run_with_id(
-        format!("public-timeline-{}", mastodon.data.base),
-        stream::channel(1, move |mut output| async move {
-            match mastodon.get_public_timeline(false, false).await {
-                Ok(statuses) => {
-                    for status in statuses {
-                        if let Err(err) = output
-                            .send(pages::public::Message::AppendStatus(status.clone()))
-                            .await
-                        {
-                            tracing::warn!("failed to send post: {}", err);
-                        }
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!("failed to get local timeline: {}", err);
-                }
+pub fn timeline(
+    mastodon: Mastodon,
+    timeline: TimelineType,
+    pagination: api::Pagination,
+) -> Subscription<pages::public::Message> {
+    let id = format!(
+        "{:?}-timeline-{}-{:?}",
+        timeline, mastodon.data.base, pagination
+    );
+    super::once(id, async move {
+        let result = match &timeline {
+            TimelineType::Local => api::public_timeline(&mastodon, true, false, &pagination).await,
+            TimelineType::Remote => api::public_timeline(&mastodon, false, true, &pagination).await,
+            TimelineType::Hashtag(hashtag) => {
+                api::hashtag_timeline(&mastodon, hashtag, &pagination).await
             }
+        };
 
-            std::future::pending().await
-        }),
-    )
-}
-
-pub fn local_timeline(mastodon: Mastodon) -> Subscription<pages::public::Message> {
-    Subscription::run_with_id(
-        format!("local-timeline-{}", mastodon.data.base),
-        stream::channel(1, move |mut output| async move {
-            match mastodon.get_public_timeline(true, false).await {
-                Ok(statuses) => {
-                    for status in statuses {
-                        if let Err(err) = output
-                            .send(pages::public::Message::AppendStatus(status.clone()))
-                            .await
-                        {
-                            tracing::warn!("failed to send post: {}", err);
-                        }
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!("failed to get local timeline: {}", err);
-                }
+        match result {
+            Ok(statuses) => pages::public::Message::Loaded(pagination, statuses),
+            Err(err) => {
+                tracing::warn!("failed to get {:?} timeline: {}", timeline, err);
+                pages::public::Message::LoadFailed(err.user_message())
             }
-
-            std::future::pending().await
-        }),
-    )
-}
-
-pub fn remote_timeline(mastodon: Mastodon) -> Subscription<pages::public::Message> {
-    Subscription::run_with_id(
-        format!("remote-timeline-{}", mastodon.data.base),
-        stream::channel(1, move |mut output| async move {
-            match mastodon.get_public_timeline(false, true).await {
-                Ok(statuses) => {
-                    for status in statuses {
-                        if let Err(err) = output
-                            .send(pages::public::Message::AppendStatus(status.clone()))
-                            .await
-                        {
-                            tracing::warn!("failed to send post: {}", err);
-                        }
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!("failed to get local timeline: {}", err);
-                }
-            }
-
-            std::future::pending().await
-        }),
-    )
+        }
+    })
 }
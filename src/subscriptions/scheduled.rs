@@ -0,0 +1,17 @@
+use cosmic::iced::Subscription;
+use mastodon_async::Mastodon;
+
+use crate::{api, pages};
+
+pub fn scheduled(mastodon: Mastodon) -> Subscription<pages::scheduled::Message> {
+    let id = format!("scheduled-{}", mastodon.data.base);
+    super::once(id, async move {
+        match api::scheduled_statuses(&mastodon).await {
+            Ok(statuses) => pages::scheduled::Message::Loaded(statuses),
+            Err(err) => {
+                tracing::warn!("failed to get scheduled statuses: {}", err);
+                pages::scheduled::Message::LoadFailed(err.user_message())
+            }
+        }
+    })
+}
@@ -0,0 +1,17 @@
+use cosmic::iced::Subscription;
+use mastodon_async::Mastodon;
+
+use crate::{api, pages};
+
+pub fn suggestions(mastodon: Mastodon) -> Subscription<pages::suggestions::Message> {
+    let id = format!("suggestions-{}", mastodon.data.base);
+    super::once(id, async move {
+        match api::suggestions(&mastodon).await {
+            Ok(accounts) => pages::suggestions::Message::Loaded(accounts),
+            Err(err) => {
+                tracing::warn!("failed to get suggestions: {}", err);
+                pages::suggestions::Message::LoadFailed(err.user_message())
+            }
+        }
+    })
+}
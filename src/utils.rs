@@ -1,38 +1,108 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    time::Duration,
+};
 
 use cosmic::{
     iced_core::image,
     widget::{self, image::Handle},
 };
-use mastodon_async::prelude::*;
+use mastodon_async::{
+    entities::{
+        attachment::{Attachment, MediaType},
+        status::Emoji,
+    },
+    prelude::*,
+};
 use reqwest::Url;
 
-use crate::error::Error;
+use crate::{
+    api,
+    error::Error,
+    images::{Animation, Thumbnail},
+    outbox::Action,
+    scheduler, storage,
+};
+
+/// Statuses and notifications kept in memory before unreferenced ones are evicted.
+pub const ENTRY_LIMIT: usize = 1000;
+/// Statuses and notifications left once entries are evicted, so that eviction doesn't
+/// run again for every entry cached afterwards.
+pub const ENTRY_LOW_WATER: usize = ENTRY_LIMIT / 2;
+/// Bytes of image data kept in memory before handles are evicted.
+const HANDLE_LIMIT: usize = 64 * 1024 * 1024;
+/// Bytes of image data left once handles are evicted, so that eviction doesn't run
+/// again for every image fetched afterwards.
+const HANDLE_LOW_WATER: usize = HANDLE_LIMIT / 4 * 3;
 
 #[derive(Debug, Clone)]
 pub struct Cache {
     pub handles: HashMap<Url, Handle>,
+    pub animations: HashMap<Url, Animation>,
+    /// Whether animations play, otherwise static images are shown.
+    pub animate: bool,
+    /// Time since the app started, which picks the frame of every animation.
+    pub clock: Duration,
+    /// Handle and animation urls, oldest first.
+    handle_order: VecDeque<Url>,
+    handle_size: usize,
     pub statuses: HashMap<String, Status>,
     pub notifications: HashMap<String, Notification>,
+    pub accounts: HashMap<String, Account>,
+    /// When each account was last seen, by account id, counted in [`Cache::seen`] steps.
+    account_seen: HashMap<String, u64>,
+    /// Counts up with every account inserted.
+    seen: u64,
+    /// The static avatar of the account shown in the profile drawer, at its size there.
+    pub profile_avatar: Option<(Url, Handle)>,
+    /// Statuses with actions waiting in the outbox.
+    pub pending: HashSet<String>,
+    /// The status picked with the keyboard.
+    pub selected: Option<String>,
+    /// The id of the logged-in account, whose statuses can be edited and deleted.
+    pub me: Option<String>,
 }
 
 impl Cache {
     pub fn new() -> Self {
         Self {
             handles: HashMap::new(),
+            animations: HashMap::new(),
+            animate: true,
+            clock: Duration::ZERO,
+            handle_order: VecDeque::new(),
+            handle_size: 0,
             statuses: HashMap::new(),
             notifications: HashMap::new(),
+            accounts: HashMap::new(),
+            account_seen: HashMap::new(),
+            seen: 0,
+            profile_avatar: None,
+            pending: HashSet::new(),
+            selected: None,
+            me: None,
         }
     }
 
     pub fn insert_status(&mut self, status: Status) {
+        self.insert_account(status.account.clone());
         self.statuses.insert(status.id.to_string(), status.clone());
         if let Some(reblog) = status.reblog {
             self.statuses.insert(reblog.id.to_string(), *reblog);
         }
     }
 
+    /// Shows `action` on the status it changes before the server confirms it.
+    pub fn apply(&mut self, action: &Action) {
+        let status = action.status_id().and_then(|id| self.statuses.get_mut(&id));
+        if let Some(status) = status {
+            action.apply(status);
+        }
+    }
+
     pub fn insert_notification(&mut self, notification: Notification) {
+        self.insert_account(notification.account.clone());
         self.notifications
             .insert(notification.id.to_string(), notification.clone());
         if let Some(status) = notification.status {
@@ -40,14 +110,224 @@ impl Cache {
         }
     }
 
+    pub fn insert_account(&mut self, account: Account) {
+        let id = account.id.to_string();
+        self.seen += 1;
+        self.account_seen.insert(id.clone(), self.seen);
+        self.accounts.insert(id, account);
+    }
+
+    /// Accounts, most recently seen first.
+    pub fn recent_accounts(&self) -> impl Iterator<Item = &Account> {
+        let mut accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .map(|(id, account)| (self.account_seen.get(id).copied().unwrap_or(0), account))
+            .collect();
+        accounts.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+        accounts.into_iter().map(|(_, account)| account)
+    }
+
     pub fn insert_handle(&mut self, url: Url, handle: Handle) {
-        self.handles.insert(url, handle);
+        self.handle_size += handle_size(&handle);
+        if let Some(previous) = self.handles.insert(url.clone(), handle) {
+            self.handle_size -= handle_size(&previous);
+            self.handle_order.retain(|other| *other != url);
+        }
+        self.handle_order.push_back(url);
+    }
+
+    pub fn insert_animation(&mut self, url: Url, animation: Animation) {
+        self.handle_size += animation.frames().map(handle_size).sum::<usize>();
+        if let Some(previous) = self.animations.insert(url.clone(), animation) {
+            self.handle_size -= previous.frames().map(handle_size).sum::<usize>();
+            self.handle_order.retain(|other| *other != url);
+        }
+        self.handle_order.push_back(url);
+    }
+
+    /// The image at `url`, or its current frame if it's animated.
+    pub fn handle(&self, url: &Url) -> Option<&Handle> {
+        self.animations
+            .get(url)
+            .filter(|_| self.animate)
+            .map(|animation| animation.frame(self.clock))
+            .or_else(|| self.handles.get(url))
+    }
+
+    /// The avatar of `account`, which is only animated while animations play.
+    pub fn avatar(&self, account: &Account) -> Option<&Handle> {
+        self.handle(&account.avatar)
+            .filter(|_| self.animate)
+            .or_else(|| self.handles.get(&account.avatar_static))
+            .or_else(|| self.handles.get(&account.avatar))
+    }
+
+    /// The avatar of `account` in the profile drawer, falling back to the timeline one
+    /// until the larger one is loaded or while an animated avatar plays.
+    pub fn profile_avatar(&self, account: &Account) -> Option<&Handle> {
+        let animated = self.animate && self.animations.contains_key(&account.avatar);
+        self.profile_avatar
+            .as_ref()
+            .filter(|(url, _)| !animated && *url == account.avatar_static)
+            .map(|(_, handle)| handle)
+            .or_else(|| self.avatar(account))
+    }
+
+    /// The preview of `attachment`, which plays if it's a GIF and animations play.
+    pub fn attachment(&self, attachment: &Attachment) -> Option<&Handle> {
+        self.animated_attachment(attachment)
+            .or_else(|| self.handles.get(&attachment.preview_url))
+    }
+
+    /// The current frame of a GIF attachment, while animations play.
+    pub fn animated_attachment(&self, attachment: &Attachment) -> Option<&Handle> {
+        animated_attachment(attachment)
+            .filter(|_| self.animate)
+            .and_then(|url| self.animations.get(&url))
+            .map(|animation| animation.frame(self.clock))
+    }
+
+    /// The image of a custom emoji, which is only animated while animations play.
+    pub fn emoji(&self, emoji: &Emoji) -> Option<&Handle> {
+        self.handle(&emoji.url)
+            .filter(|_| self.animate)
+            .or_else(|| self.handles.get(&emoji.static_url))
+            .or_else(|| self.handles.get(&emoji.url))
+    }
+
+    /// The statuses and notifications kept.
+    pub fn entries(&self) -> usize {
+        self.statuses.len() + self.notifications.len()
+    }
+
+    pub fn over_limit(&self) -> bool {
+        self.statuses.len() + self.notifications.len() > ENTRY_LIMIT
+            || self.handle_size > HANDLE_LIMIT
+    }
+
+    /// Drops everything that isn't referenced, then, if the handles are over
+    /// [`HANDLE_LIMIT`], the oldest ones that aren't shown on the active page until
+    /// they fit in [`HANDLE_LOW_WATER`].
+    pub fn retain(&mut self, references: &References) {
+        self.notifications
+            .retain(|id, _| references.notifications.contains(id));
+
+        let mut statuses = references.statuses.clone();
+        statuses.extend(
+            self.notifications
+                .values()
+                .filter_map(|notification| notification.status.as_ref())
+                .map(|status| status.id.to_string()),
+        );
+        let reblogs: Vec<String> = statuses
+            .iter()
+            .filter_map(|id| self.statuses.get(id)?.reblog.as_ref())
+            .map(|reblog| reblog.id.to_string())
+            .collect();
+        statuses.extend(reblogs);
+        self.statuses.retain(|id, _| statuses.contains(id));
+
+        let accounts: HashSet<String> = self
+            .statuses
+            .values()
+            .map(|status| status.account.id.to_string())
+            .chain(
+                self.notifications
+                    .values()
+                    .map(|notification| notification.account.id.to_string()),
+            )
+            .collect();
+        self.accounts.retain(|id, _| accounts.contains(id));
+        self.account_seen.retain(|id, _| accounts.contains(id));
+
+        let images: HashSet<Url> = self
+            .statuses
+            .values()
+            .flat_map(extract_status_images)
+            .chain(
+                self.notifications
+                    .values()
+                    .flat_map(extract_notification_images),
+            )
+            .map(|(url, _)| url)
+            .chain(references.images.iter().cloned())
+            .collect();
+        self.remove_handles(|url| !images.contains(url));
+
+        if self.handle_size <= HANDLE_LIMIT {
+            return;
+        }
+        let mut size = self.handle_size;
+        let evicted: HashSet<Url> = self
+            .handle_order
+            .iter()
+            .filter(|url| !references.images.contains(*url))
+            .take_while(|url| {
+                let over = size > HANDLE_LOW_WATER;
+                size -= self.handles.get(*url).map(handle_size).unwrap_or_default();
+                size -= self
+                    .animations
+                    .get(*url)
+                    .map(|animation| animation.frames().map(handle_size).sum())
+                    .unwrap_or_default();
+                over
+            })
+            .cloned()
+            .collect();
+        self.remove_handles(|url| evicted.contains(url));
+    }
+
+    fn remove_handles(&mut self, remove: impl Fn(&Url) -> bool) {
+        let mut size = 0;
+        self.handles.retain(|url, handle| {
+            let keep = !remove(url);
+            if !keep {
+                size += handle_size(handle);
+            }
+            keep
+        });
+        self.animations.retain(|url, animation| {
+            let keep = !remove(url);
+            if !keep {
+                size += animation.frames().map(handle_size).sum::<usize>();
+            }
+            keep
+        });
+        self.handle_size -= size;
+        self.handle_order.retain(|url| !remove(url));
     }
 
     pub fn clear(&mut self) {
         self.statuses.clear();
         self.notifications.clear();
+        self.accounts.clear();
+        self.account_seen.clear();
         self.handles.clear();
+        self.animations.clear();
+        self.handle_order.clear();
+        self.handle_size = 0;
+        self.profile_avatar = None;
+        self.pending.clear();
+        self.selected = None;
+        self.me = None;
+    }
+}
+
+/// What the pages still show, everything else can be dropped from the [`Cache`].
+#[derive(Debug, Default)]
+pub struct References {
+    pub statuses: HashSet<String>,
+    pub notifications: HashSet<String>,
+    /// Images on the active page, which are never evicted.
+    pub images: HashSet<Url>,
+}
+
+fn handle_size(handle: &Handle) -> usize {
+    match handle {
+        Handle::Bytes(_, bytes) => bytes.len(),
+        Handle::Rgba { pixels, .. } => pixels.len(),
+        Handle::Path(..) => 0,
     }
 }
 
@@ -61,71 +341,126 @@ pub fn fallback_handle() -> widget::image::Handle {
     image::Handle::from_bytes(include_bytes!("../assets/missing.png").to_vec())
 }
 
-pub async fn get(url: impl ToString) -> Result<Handle, Error> {
-    let response = reqwest::get(url.to_string()).await?;
-    match response.error_for_status() {
-        Ok(response) => {
-            let bytes = response.bytes().await?;
-            let handle = Handle::from_bytes(bytes.to_vec());
-            Ok(handle)
+/// Downloads the bytes of an image, or reads them from the disk cache.
+pub async fn get(url: &Url) -> Result<Vec<u8>, Error> {
+    if let Some(bytes) = storage::load_image(url).await {
+        return Ok(bytes);
+    }
+
+    let request = api::CLIENT.get(url.clone()).build()?;
+    let response = api::check(scheduler::send(&api::CLIENT, request).await?)?;
+    let bytes = response.bytes().await?;
+    if let Err(err) = storage::save_image(url, &bytes).await {
+        tracing::warn!("failed to cache image: {}", err);
+    }
+    Ok(bytes.to_vec())
+}
+
+/// The static avatar and header of `account`, its animated avatar if it has one and
+/// the custom emoji in its name and bio.
+pub fn extract_account_images(account: &Account) -> Vec<(Url, Thumbnail)> {
+    let mut urls = Vec::new();
+    urls.push((account.avatar_static.clone(), Thumbnail::Avatar));
+    if account.avatar != account.avatar_static {
+        urls.push((account.avatar.clone(), Thumbnail::AnimatedAvatar));
+    }
+    urls.push((account.header.clone(), Thumbnail::Header));
+    urls.extend(extract_emoji_images(&account.emojis));
+    urls
+}
+
+/// The static image of every custom emoji, and the animated one if it differs.
+pub fn extract_emoji_images(emojis: &[Emoji]) -> Vec<(Url, Thumbnail)> {
+    let mut urls = Vec::new();
+    for emoji in emojis {
+        urls.push((emoji.static_url.clone(), Thumbnail::Emoji));
+        if emoji.url != emoji.static_url {
+            urls.push((emoji.url.clone(), Thumbnail::AnimatedEmoji));
         }
-        Err(err) => Err(err.into()),
     }
+    urls
+}
+
+/// The preview of every attachment.
+pub fn extract_attachment_images(attachments: &[Attachment]) -> Vec<(Url, Thumbnail)> {
+    attachments
+        .iter()
+        .map(|attachment| (attachment.preview_url.clone(), Thumbnail::Preview))
+        .collect()
 }
 
-pub fn extract_status_images(status: &Status) -> Vec<Url> {
+/// The GIFs played in place of the previews of a status. They take far more memory
+/// than a preview, so they're only fetched for statuses that are shown.
+pub fn extract_animated_images(status: &Status) -> Vec<(Url, Thumbnail)> {
+    status
+        .reblog
+        .iter()
+        .flat_map(|reblog| &reblog.media_attachments)
+        .chain(&status.media_attachments)
+        .filter_map(animated_attachment)
+        .map(|url| (url, Thumbnail::AnimatedPreview))
+        .collect()
+}
+
+/// The GIF an image or GIFV attachment plays. Servers convert uploaded GIFs to videos,
+/// which can't be decoded here, but remote ones often still link the original GIF.
+pub fn animated_attachment(attachment: &Attachment) -> Option<Url> {
+    if !matches!(attachment.media_type, MediaType::Image | MediaType::Gifv) {
+        return None;
+    }
+    [attachment.url.as_ref(), attachment.remote_url.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|url| Url::parse(url.as_str()).ok())
+        .find(|url| url.path().to_lowercase().ends_with(".gif"))
+}
+
+pub fn extract_status_images(status: &Status) -> Vec<(Url, Thumbnail)> {
     let mut urls = Vec::new();
-    urls.push(status.account.avatar.clone());
-    urls.push(status.account.header.clone());
+    urls.extend(extract_account_images(&status.account));
+    urls.extend(extract_emoji_images(&status.emojis));
 
     if let Some(reblog) = &status.reblog {
-        urls.push(reblog.account.avatar.clone());
-        urls.push(reblog.account.header.clone());
+        urls.extend(extract_account_images(&reblog.account));
+        urls.extend(extract_emoji_images(&reblog.emojis));
         if let Some(card) = &reblog.card {
             if let Some(image) = &card.image {
                 if let Ok(url) = Url::from_str(image) {
-                    urls.push(url);
+                    urls.push((url, Thumbnail::Preview));
                 }
             }
         }
-        for attachment in &reblog.media_attachments {
-            urls.push(attachment.preview_url.clone());
-        }
+        urls.extend(extract_attachment_images(&reblog.media_attachments));
     }
 
     if let Some(card) = &status.card {
         if let Some(image) = &card.image {
             if let Ok(url) = Url::from_str(image) {
-                urls.push(url);
+                urls.push((url, Thumbnail::Preview));
             }
         }
     }
 
-    for attachment in &status.media_attachments {
-        urls.push(attachment.preview_url.clone());
-    }
+    urls.extend(extract_attachment_images(&status.media_attachments));
 
     urls
 }
 
-pub fn extract_notification_images(notification: &Notification) -> Vec<Url> {
+pub fn extract_notification_images(notification: &Notification) -> Vec<(Url, Thumbnail)> {
     let mut urls = Vec::new();
-    urls.push(notification.account.avatar.clone());
-    urls.push(notification.account.header.clone());
+    urls.extend(extract_account_images(&notification.account));
 
     if let Some(status) = &notification.status {
-        urls.push(status.account.avatar.clone());
-        urls.push(status.account.header.clone());
+        urls.extend(extract_account_images(&status.account));
+        urls.extend(extract_emoji_images(&status.emojis));
         if let Some(card) = &status.card {
             if let Some(image) = &card.image {
                 if let Ok(url) = Url::from_str(image) {
-                    urls.push(url);
+                    urls.push((url, Thumbnail::Preview));
                 }
             }
         }
-        for attachment in &status.media_attachments {
-            urls.push(attachment.preview_url.clone());
-        }
+        urls.extend(extract_attachment_images(&status.media_attachments));
     }
     urls
 }
@@ -0,0 +1,283 @@
+//! Shows the attachments of a status in a separate window, where images can be zoomed
+//! with the scroll wheel and panned by dragging. GIFs and GIFVs play in the window when
+//! the GIF behind them is available. Videos and audio play in the window with the
+//! `video` feature, and open with the default application without it.
+
+use cosmic::{
+    app::command::Task,
+    iced::{keyboard::key::Named, window, Alignment, ContentFit, Length},
+    iced_widget::{image::viewer, Stack},
+    widget, Apply, Element,
+};
+use mastodon_async::entities::attachment::{Attachment, MediaType};
+use reqwest::Url;
+
+use crate::{
+    app, fl,
+    images::Thumbnail,
+    utils::{self, Cache},
+};
+
+#[cfg(feature = "video")]
+mod player;
+
+pub struct Viewer {
+    pub window: window::Id,
+    attachments: Vec<Attachment>,
+    index: usize,
+    /// The video or audio of the current attachment, while it plays.
+    #[cfg(feature = "video")]
+    player: Option<player::Player>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Previous,
+    Next,
+    /// Plays a video, GIFV or audio attachment, with the default application when it
+    /// can't be played in the window.
+    Play(Url),
+    #[cfg(feature = "video")]
+    TogglePause,
+    #[cfg(feature = "video")]
+    Ended,
+    /// Opens the current attachment with the default application.
+    Open(Url),
+    Close,
+}
+
+impl Viewer {
+    pub fn new(window: window::Id, attachments: Vec<Attachment>, index: usize) -> Self {
+        let index = index.min(attachments.len().saturating_sub(1));
+        Self {
+            window,
+            attachments,
+            index,
+            #[cfg(feature = "video")]
+            player: None,
+        }
+    }
+
+    pub fn view<'a>(&'a self, cache: &'a Cache) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+        let Some(attachment) = self.attachments.get(self.index) else {
+            return widget::text(fl!("no-media")).into();
+        };
+
+        let toolbar = widget::row()
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .tooltip(fl!("previous"))
+                    .on_press_maybe((self.index > 0).then_some(Message::Previous)),
+            )
+            .push(widget::text(format!(
+                "{} / {}",
+                self.index + 1,
+                self.attachments.len()
+            )))
+            .push(
+                widget::button::icon(widget::icon::from_name("go-next-symbolic"))
+                    .tooltip(fl!("next"))
+                    .on_press_maybe(
+                        (self.index + 1 < self.attachments.len()).then_some(Message::Next),
+                    ),
+            )
+            .push(widget::horizontal_space())
+            .push_maybe(attachment.url.clone().map(|url| {
+                let label = match attachment.media_type {
+                    MediaType::Image | MediaType::Unknown => fl!("open-original"),
+                    MediaType::Video | MediaType::Gifv | MediaType::Audio => {
+                        fl!("open-externally")
+                    }
+                };
+                widget::button::standard(label).on_press(Message::Open(url))
+            }))
+            .push(
+                widget::button::icon(widget::icon::from_name("window-close-symbolic"))
+                    .tooltip(fl!("close"))
+                    .on_press(Message::Close),
+            )
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs);
+
+        #[cfg(feature = "video")]
+        if let Some(player) = &self.player {
+            let media = player.view(cache.handle(&attachment.preview_url));
+            return layout(toolbar, media, attachment);
+        }
+
+        let playing = cache.animated_attachment(attachment);
+        let media: Element<_> = match (&attachment.media_type, playing) {
+            (MediaType::Image | MediaType::Unknown, playing) => {
+                let original = attachment.url.as_ref().and_then(|url| cache.handle(url));
+                let image = playing
+                    .or(original)
+                    .or_else(|| cache.handle(&attachment.preview_url));
+                match image {
+                    Some(handle) => viewer(handle.clone())
+                        .min_scale(1.0)
+                        .max_scale(10.0)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .into(),
+                    None => widget::text(fl!("loading"))
+                        .apply(widget::container)
+                        .center(Length::Fill)
+                        .into(),
+                }
+            }
+            (MediaType::Gifv, Some(handle)) => widget::image(handle)
+                .content_fit(ContentFit::Contain)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            (MediaType::Video | MediaType::Gifv | MediaType::Audio, _) => {
+                let play = attachment.url.clone().map(|url| {
+                    widget::button::icon(widget::icon::from_name("media-playback-start-symbolic"))
+                        .large()
+                        .class(cosmic::theme::Button::Suggested)
+                        .tooltip(fl!("play"))
+                        .on_press(Message::Play(url))
+                        .apply(widget::container)
+                        .center(Length::Fill)
+                });
+                Stack::new()
+                    .push_maybe(cache.handle(&attachment.preview_url).map(|handle| {
+                        widget::image(handle)
+                            .content_fit(ContentFit::Contain)
+                            .width(Length::Fill)
+                            .height(Length::Fill)
+                    }))
+                    .push_maybe(play)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into()
+            }
+        };
+
+        layout(toolbar, media, attachment)
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        match message {
+            Message::Previous => self.select(self.index.saturating_sub(1)),
+            Message::Next => {
+                self.select((self.index + 1).min(self.attachments.len().saturating_sub(1)))
+            }
+            Message::Play(url) => self.play(url),
+            #[cfg(feature = "video")]
+            Message::TogglePause => {
+                if let Some(player) = &mut self.player {
+                    player.toggle();
+                }
+                Task::none()
+            }
+            #[cfg(feature = "video")]
+            Message::Ended => {
+                if let Some(player) = &mut self.player {
+                    player.ended();
+                }
+                Task::none()
+            }
+            Message::Open(url) => cosmic::task::message(app::Message::Open(url.to_string())),
+            Message::Close => cosmic::task::message(app::Message::CloseViewer),
+        }
+    }
+
+    /// Shows the attachment at `index`, stopping what the previous one played.
+    fn select(&mut self, index: usize) -> Task<app::Message> {
+        if index != self.index {
+            self.index = index;
+            #[cfg(feature = "video")]
+            {
+                self.player = None;
+            }
+        }
+        cosmic::task::message(app::Message::Fetch(self.images()))
+    }
+
+    #[cfg(feature = "video")]
+    fn play(&mut self, url: Url) -> Task<app::Message> {
+        let Some(attachment) = self.attachments.get(self.index) else {
+            return Task::none();
+        };
+        match player::Player::new(&url, &attachment.media_type) {
+            Ok(player) => {
+                self.player = Some(player);
+                Task::none()
+            }
+            Err(err) => {
+                tracing::warn!("Opening {} with the default application: {}", url, err);
+                cosmic::task::message(app::Message::Open(url.to_string()))
+            }
+        }
+    }
+
+    #[cfg(not(feature = "video"))]
+    fn play(&mut self, url: Url) -> Task<app::Message> {
+        cosmic::task::message(app::Message::Open(url.to_string()))
+    }
+
+    /// Maps the arrow keys to navigation, space to pausing playback and escape to closing
+    /// the viewer.
+    pub fn on_key(&self, key: Named) -> Option<Message> {
+        match key {
+            Named::ArrowLeft => Some(Message::Previous),
+            Named::ArrowRight => Some(Message::Next),
+            Named::Escape => Some(Message::Close),
+            #[cfg(feature = "video")]
+            Named::Space if self.player.is_some() => Some(Message::TogglePause),
+            _ => None,
+        }
+    }
+
+    /// The full size image or the GIF of the current attachment and its neighbours, so
+    /// navigating doesn't wait for downloads.
+    pub fn images(&self) -> Vec<(Url, Thumbnail)> {
+        let start = self.index.saturating_sub(1);
+        let attachments = self
+            .attachments
+            .iter()
+            .skip(start)
+            .take(self.index + 2 - start);
+        let mut urls = Vec::new();
+        for attachment in attachments {
+            if let Some(url) = utils::animated_attachment(attachment) {
+                urls.push((url, Thumbnail::AnimatedPreview));
+            }
+            if let (MediaType::Image | MediaType::Unknown, Some(url)) =
+                (&attachment.media_type, &attachment.url)
+            {
+                urls.push((url.clone(), Thumbnail::Original));
+            }
+        }
+        urls
+    }
+}
+
+/// Puts the toolbar above and the description below the media.
+fn layout<'a>(
+    toolbar: impl Into<Element<'a, Message>>,
+    media: Element<'a, Message>,
+    attachment: &'a Attachment,
+) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let description = match &attachment.description {
+        Some(description) if !description.trim().is_empty() => widget::text(description.clone()),
+        _ => widget::text::caption(fl!("no-description")),
+    };
+
+    widget::column()
+        .push(toolbar)
+        .push(media)
+        .push(
+            description
+                .apply(widget::container)
+                .padding(spacing.space_xs)
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        )
+        .padding(spacing.space_xs)
+        .spacing(spacing.space_xs)
+        .into()
+}
@@ -0,0 +1,139 @@
+//! Plays videos, GIFVs and audio in the media viewer with GStreamer.
+
+use cosmic::{
+    iced::{ContentFit, Length},
+    widget::{self, image::Handle},
+    Element,
+};
+use gstreamer::prelude::*;
+use iced_video_player::{Video, VideoPlayer};
+use mastodon_async::entities::attachment::MediaType;
+use reqwest::Url;
+
+use super::Message;
+use crate::{error::Error, fl};
+
+pub enum Player {
+    /// Videos and GIFVs, where GIFVs loop like the GIFs they replace.
+    Video(Video),
+    /// Audio, shown with the preview of the attachment while it plays.
+    Audio(gstreamer::Element),
+}
+
+impl Player {
+    /// Starts playing `url`, which is decoded as audio for audio attachments.
+    pub fn new(url: &Url, media_type: &MediaType) -> Result<Self, Error> {
+        if let MediaType::Audio = media_type {
+            gstreamer::init().map_err(playback)?;
+            let playbin = gstreamer::ElementFactory::make("playbin")
+                .property("uri", url.as_str())
+                .build()
+                .map_err(playback)?;
+            playbin
+                .set_state(gstreamer::State::Playing)
+                .map_err(playback)?;
+            return Ok(Player::Audio(playbin));
+        }
+        let mut video = Video::new(url).map_err(playback)?;
+        video.set_looping(matches!(media_type, MediaType::Gifv));
+        Ok(Player::Video(video))
+    }
+
+    pub fn paused(&self) -> bool {
+        match self {
+            Player::Video(video) => video.paused(),
+            Player::Audio(playbin) => playbin.current_state() != gstreamer::State::Playing,
+        }
+    }
+
+    /// Pauses or resumes, starting over when the end was reached.
+    pub fn toggle(&mut self) {
+        let paused = self.paused();
+        match self {
+            Player::Video(video) if video.eos() => {
+                if let Err(err) = video.restart_stream() {
+                    tracing::error!("Failed to restart playback: {}", err);
+                }
+            }
+            Player::Video(video) => video.set_paused(!paused),
+            Player::Audio(playbin) => {
+                let ended = playbin
+                    .bus()
+                    .and_then(|bus| bus.pop_filtered(&[gstreamer::MessageType::Eos]))
+                    .is_some();
+                if ended {
+                    if let Err(err) =
+                        playbin.seek_simple(gstreamer::SeekFlags::FLUSH, gstreamer::ClockTime::ZERO)
+                    {
+                        tracing::error!("Failed to restart playback: {}", err);
+                    }
+                }
+                let state = if paused || ended {
+                    gstreamer::State::Playing
+                } else {
+                    gstreamer::State::Paused
+                };
+                if let Err(err) = playbin.set_state(state) {
+                    tracing::error!("Failed to change playback: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Shows the play button again once a video that doesn't loop is over.
+    pub fn ended(&mut self) {
+        if let Player::Video(video) = self {
+            video.set_paused(true);
+        }
+    }
+
+    pub fn view<'a>(&'a self, preview: Option<&'a Handle>) -> Element<'a, Message> {
+        let media: Option<Element<_>> = match self {
+            Player::Video(video) => Some(
+                VideoPlayer::new(video)
+                    .content_fit(ContentFit::Contain)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .on_end_of_stream(Message::Ended)
+                    .into(),
+            ),
+            Player::Audio(_) => preview.map(|handle| {
+                widget::image(handle)
+                    .content_fit(ContentFit::Contain)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into()
+            }),
+        };
+        let (icon, tooltip) = if self.paused() {
+            ("media-playback-start-symbolic", fl!("play"))
+        } else {
+            ("media-playback-pause-symbolic", fl!("pause"))
+        };
+        let toggle = widget::button::icon(widget::icon::from_name(icon))
+            .tooltip(tooltip)
+            .on_press(Message::TogglePause);
+
+        widget::column()
+            .push(media.unwrap_or_else(|| widget::vertical_space().into()))
+            .push(widget::container(toggle).center_x(Length::Fill))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        // Videos stop themselves, a playbin keeps playing until it's shut down.
+        if let Player::Audio(playbin) = self {
+            if let Err(err) = playbin.set_state(gstreamer::State::Null) {
+                tracing::error!("Failed to stop playback: {}", err);
+            }
+        }
+    }
+}
+
+fn playback(err: impl std::fmt::Display) -> Error {
+    Error::Playback(err.to_string())
+}
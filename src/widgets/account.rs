@@ -3,32 +3,31 @@ use cosmic::{
     app::command::Task,
     iced::{alignment::Horizontal, ContentFit, Length},
     iced_widget::Stack,
-    widget::{self, image::Handle},
-    Apply, Element,
+    widget, Apply, Element,
 };
 use mastodon_async::prelude::Account;
 use reqwest::Url;
-use std::{collections::HashMap, str::FromStr};
+use std::str::FromStr;
 
-use crate::app;
+use crate::{app, html::Link, utils::Cache};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Open(Url),
+    /// Opens the profile of an account mentioned in the bio by its url.
+    OpenMention(Url),
+    OpenHashtag(String),
 }
 
-pub fn account<'a>(
-    account: &'a Account,
-    handles: &'a HashMap<Url, Handle>,
-) -> Element<'a, Message> {
+pub fn account<'a>(account: &'a Account, cache: &'a Cache) -> Element<'a, Message> {
     let spacing = cosmic::theme::active().cosmic().spacing;
 
-    let header = handles.get(&account.header).map(|handle| {
+    let header = cache.handle(&account.header).map(|handle| {
         widget::image(handle)
             .content_fit(ContentFit::Cover)
             .height(120.0)
     });
-    let avatar = handles.get(&account.avatar).map(|handle| {
+    let avatar = cache.profile_avatar(account).map(|handle| {
         widget::container(
             widget::button::image(handle)
                 .on_press(Message::Open(account.avatar.clone()))
@@ -37,28 +36,33 @@ pub fn account<'a>(
         .center(Length::Fill)
     });
     let stack = Stack::new().push_maybe(header).push_maybe(avatar);
-    let display_name = widget::text(&account.display_name).size(18);
+    let display_name = super::inline::text(&account.display_name, &account.emojis, cache, 18);
     let username = widget::button::link(format!("@{}", account.username))
         .on_press(Message::Open(account.url.clone()));
-    let bio = (!account.note.is_empty()).then_some(widget::text(
-        html2text::config::rich()
-            .string_from_read(account.note.as_bytes(), 700)
-            .unwrap(),
-    ));
-    let joined = widget::text::caption(format!(
-        "Joined on {}",
-        account
-            .created_at
-            .format(&time::format_description::parse("[day] [month repr:short] [year]").unwrap())
-            .unwrap()
-    ));
+    let bio = (!account.note.is_empty()).then(|| {
+        super::inline::html(
+            &account.note,
+            &account.emojis,
+            cache,
+            14,
+            |link| match link {
+                Link::Hashtag(hashtag) => Message::OpenHashtag(hashtag.clone()),
+                Link::Url(url) => Message::Open(url.clone()),
+                Link::Mention(url) => Message::OpenMention(url.clone()),
+            },
+        )
+    });
+    let joined = time::format_description::parse("[day] [month repr:short] [year]")
+        .ok()
+        .and_then(|format| account.created_at.format(&format).ok())
+        .map(|created_at| widget::text::caption(format!("Joined on {}", created_at)));
     let fields: Vec<Element<_>> = account
         .fields
         .iter()
         .map(|field| {
             let value = html2text::config::rich()
                 .string_from_read(field.value.as_bytes(), 700)
-                .unwrap();
+                .unwrap_or_else(|_| field.value.clone());
             widget::column()
                 .push(widget::text(field.name.capitalize()))
                 .push(widget::text(value.clone()).class(cosmic::style::Text::Accent))
@@ -102,7 +106,7 @@ pub fn account<'a>(
         .push(display_name)
         .push(username)
         .push_maybe(bio)
-        .push(joined)
+        .push_maybe(joined)
         .push(info)
         .push_maybe((!fields.is_empty()).then_some(widget::settings::section().extend(fields)))
         .align_x(Horizontal::Center)
@@ -115,13 +119,19 @@ pub fn account<'a>(
 }
 
 pub fn update(message: Message) -> Task<app::Message> {
-    let tasks = vec![];
+    let mut tasks = vec![];
     match message {
         Message::Open(url) => {
             if let Err(err) = open::that_detached(url.to_string()) {
                 tracing::error!("{err}");
             }
         }
+        Message::OpenMention(url) => {
+            tasks.push(cosmic::task::message(app::Message::OpenAccountUrl(url)))
+        }
+        Message::OpenHashtag(hashtag) => {
+            tasks.push(cosmic::task::message(app::Message::OpenHashtag(hashtag)))
+        }
     }
     Task::batch(tasks)
 }
@@ -0,0 +1,265 @@
+//! Rich text with custom emoji shown inline, in place of their `:shortcode:`.
+
+use cosmic::{
+    iced::{font, padding, Color, Font, Length},
+    iced_widget::{rich_text, text::Span},
+    widget, Element,
+};
+use mastodon_async::entities::status::Emoji;
+
+use crate::{
+    html::{self, Link},
+    utils::Cache,
+};
+
+/// A run of text or a custom emoji.
+#[derive(Debug, Clone, Copy)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Emoji(&'a Emoji),
+}
+
+/// Splits `text` at the shortcodes of `emojis`, leaving unknown shortcodes as text.
+pub fn segments<'a>(text: &'a str, emojis: &'a [Emoji]) -> Vec<Segment<'a>> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut rest = text;
+    while let Some(open) = rest.find(':') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find(':') else {
+            break;
+        };
+        let offset = text.len() - rest.len();
+        match emojis
+            .iter()
+            .find(|emoji| emoji.shortcode == after[..close])
+        {
+            Some(emoji) => {
+                if start < offset + open {
+                    segments.push(Segment::Text(&text[start..offset + open]));
+                }
+                segments.push(Segment::Emoji(emoji));
+                rest = &after[close + 1..];
+                start = text.len() - rest.len();
+            }
+            // The closing colon may open the next shortcode.
+            None => rest = &after[close..],
+        }
+    }
+    if start < text.len() {
+        segments.push(Segment::Text(&text[start..]));
+    }
+    segments
+}
+
+/// Renders `text` at `size`, wrapping word by word when it contains custom emoji.
+pub fn text<'a, M: 'a>(
+    text: &str,
+    emojis: &'a [Emoji],
+    cache: &'a Cache,
+    size: u16,
+) -> Element<'a, M> {
+    let segments = segments(text, emojis);
+    if !segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Emoji(_)))
+    {
+        return widget::text(text.to_string()).size(size).into();
+    }
+
+    let mut lines: Vec<Vec<Element<'a, M>>> = vec![vec![]];
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => push_words(&mut lines, text, |word| {
+                widget::text(word.to_string()).size(size).into()
+            }),
+            Segment::Emoji(emoji) => lines
+                .last_mut()
+                .unwrap()
+                .push(self::emoji(emoji, cache, size)),
+        }
+    }
+    wrap(lines, size)
+}
+
+/// Renders the HTML of a status or bio, turning its links into messages with `on_link`.
+pub fn html<'a, M: Clone + 'static>(
+    html: &str,
+    emojis: &'a [Emoji],
+    cache: &'a Cache,
+    size: u16,
+    on_link: impl Fn(&Link) -> M,
+) -> Element<'a, M> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let paragraphs = html::parse(html).into_iter().map(|paragraph| {
+        let content = self::paragraph(&paragraph, emojis, cache, size, &on_link);
+        if paragraph.quote {
+            widget::container(content)
+                .padding(padding::left(spacing.space_s))
+                .into()
+        } else {
+            content
+        }
+    });
+
+    widget::column().extend(paragraphs).spacing(size / 2).into()
+}
+
+fn paragraph<'a, M: Clone + 'static>(
+    paragraph: &html::Paragraph,
+    emojis: &'a [Emoji],
+    cache: &'a Cache,
+    size: u16,
+    on_link: &impl Fn(&Link) -> M,
+) -> Element<'a, M> {
+    let has_emoji = paragraph.spans.iter().any(|span| {
+        segments(&span.text, emojis)
+            .iter()
+            .any(|segment| matches!(segment, Segment::Emoji(_)))
+    });
+    if !has_emoji {
+        let spans: Vec<Span<'a, M, Font>> = paragraph
+            .spans
+            .iter()
+            .map(|span| self::span(span.text.clone(), span, on_link))
+            .collect();
+        return rich_text(spans).size(size).into();
+    }
+
+    // Rich text can't hold images, so the paragraph wraps word by word instead.
+    let mut lines: Vec<Vec<Element<'a, M>>> = vec![vec![]];
+    for span in &paragraph.spans {
+        for segment in segments(&span.text, emojis) {
+            match segment {
+                Segment::Text(text) => push_words(&mut lines, text, |word| {
+                    rich_text([self::span(word.to_string(), span, on_link)])
+                        .size(size)
+                        .into()
+                }),
+                Segment::Emoji(emoji) => lines
+                    .last_mut()
+                    .unwrap()
+                    .push(self::emoji(emoji, cache, size)),
+            }
+        }
+    }
+    wrap(lines, size)
+}
+
+fn span<'a, M: Clone + 'static>(
+    text: String,
+    span: &html::Span,
+    on_link: &impl Fn(&Link) -> M,
+) -> Span<'a, M, Font> {
+    let mut font = if span.style.code {
+        cosmic::font::mono()
+    } else if span.style.bold {
+        cosmic::font::bold()
+    } else {
+        cosmic::font::default()
+    };
+    if span.style.italic {
+        font.style = font::Style::Italic;
+    }
+    let link = span.link.as_ref().map(on_link);
+    let color = link
+        .is_some()
+        .then(|| Color::from(cosmic::theme::active().cosmic().accent_text_color()));
+
+    cosmic::iced_widget::span(text)
+        .font(font)
+        .strikethrough(span.style.strikethrough)
+        .color_maybe(color)
+        .link_maybe(link)
+}
+
+/// Adds the words of `text` to the last line, starting a new line at every line break.
+fn push_words<'a, M>(
+    lines: &mut Vec<Vec<Element<'a, M>>>,
+    text: &str,
+    word: impl Fn(&str) -> Element<'a, M>,
+) {
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            lines.push(vec![]);
+        }
+        lines
+            .last_mut()
+            .unwrap()
+            .extend(line.split_whitespace().map(&word));
+    }
+}
+
+fn wrap<'a, M: 'a>(lines: Vec<Vec<Element<'a, M>>>, size: u16) -> Element<'a, M> {
+    widget::column()
+        .extend(lines.into_iter().map(|line| {
+            widget::flex_row(line)
+                .column_spacing(size / 4)
+                .row_spacing(size / 4)
+                .into()
+        }))
+        .into()
+}
+
+/// A custom emoji a little taller than text at `size`.
+pub fn emoji<'a, M: 'a>(emoji: &'a Emoji, cache: &'a Cache, size: u16) -> Element<'a, M> {
+    let height = Length::Fixed(f32::from(size) * 1.25);
+    match cache.emoji(emoji) {
+        Some(handle) => widget::image(handle)
+            .height(height)
+            .width(Length::Shrink)
+            .into(),
+        None => widget::text(format!(":{}:", emoji.shortcode))
+            .size(size)
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emoji(shortcode: &str) -> Emoji {
+        let url = format!("https://example.com/{}.png", shortcode);
+        serde_json::from_value(serde_json::json!({
+            "shortcode": shortcode,
+            "url": url,
+            "static_url": url,
+            "visible_in_picker": true,
+        }))
+        .unwrap()
+    }
+
+    fn parts(text: &str, emojis: &[Emoji]) -> Vec<String> {
+        segments(text, emojis)
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.to_string(),
+                Segment::Emoji(emoji) => format!("<{}>", emoji.shortcode),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_at_known_shortcodes() {
+        let emojis = [emoji("blobcat"), emoji("party")];
+        assert_eq!(
+            parts("hi :blobcat: and :party:", &emojis),
+            ["hi ", "<blobcat>", " and ", "<party>"]
+        );
+        assert_eq!(parts(":blobcat::party:", &emojis), ["<blobcat>", "<party>"]);
+    }
+
+    #[test]
+    fn leaves_unknown_shortcodes_as_text() {
+        let emojis = [emoji("blobcat")];
+        assert_eq!(parts("at 10:30 :nope:", &emojis), ["at 10:30 :nope:"]);
+        assert_eq!(parts("no emoji", &[]), ["no emoji"]);
+    }
+
+    #[test]
+    fn a_closing_colon_may_open_the_next_shortcode() {
+        let emojis = [emoji("blobcat")];
+        assert_eq!(parts("time:12:blobcat:", &emojis), ["time:12", "<blobcat>"]);
+    }
+}
@@ -4,3 +4,4 @@ pub mod notification;
 pub use notification::notification;
 pub mod account;
 pub use account::account;
+pub mod inline;
@@ -1,52 +1,80 @@
-use cosmic::{widget, Element};
+use cosmic::{iced::Alignment, widget, Element};
 use mastodon_async::prelude::{notification::Type, Notification};
 
-use crate::utils::{self, Cache};
+use crate::{
+    fl,
+    utils::{self, Cache},
+};
 
 use super::status::StatusOptions;
 
+/// Avatars shown for a group of notifications, the rest are only counted.
+const GROUP_AVATARS: usize = 8;
+
 #[derive(Debug, Clone)]
 pub enum Message {
     Status(crate::widgets::status::Message),
+    /// Accepts the follow request of an account by its id.
+    AcceptFollowRequest(String),
+    RejectFollowRequest(String),
+    /// Opens the reports and sign-ups waiting for moderators.
+    OpenModeration,
 }
 
-pub fn notification<'a>(notification: &'a Notification, cache: &'a Cache) -> Element<'a, Message> {
-    let spacing = cosmic::theme::active().cosmic().spacing;
-
-    let display_name = notification.account.display_name.clone();
+/// What happened, e.g. "Alice and 12 others favourited your post" for a group.
+pub fn summary(notification: &Notification, others: usize) -> String {
+    let name = notification.account.display_name.as_str();
+    match notification.notification_type {
+        Type::Mention => fl!("notification-mention", name = name),
+        Type::Reblog => fl!("notification-reblog", name = name, others = others),
+        Type::Favourite => fl!("notification-favourite", name = name, others = others),
+        Type::Follow => fl!("notification-follow", name = name),
+        Type::FollowRequest => fl!("notification-follow-request", name = name),
+        Type::Poll => fl!("notification-poll"),
+        Type::Status => fl!("notification-status", name = name),
+        Type::Update => fl!("notification-update", name = name),
+        Type::SignUp => fl!("notification-sign-up", name = name),
+        Type::Report => fl!("notification-report"),
+    }
+}
 
-    let action = match notification.notification_type {
-        Type::Mention => format!("{} mentioned you", display_name),
-        Type::Reblog => format!("{} boosted", display_name),
-        Type::Favourite => format!("{} liked", display_name),
-        Type::Follow => {
-            format!("{} followed you", display_name)
-        }
-        Type::FollowRequest => format!("{} requested to follow you", display_name),
-        Type::Poll => {
-            format!("{} created a poll", display_name)
-        }
-        Type::Status => format!("{} has posted a status", display_name),
-        Type::Update => "A post has been edited".to_string(),
-        Type::SignUp => "Someone signed up (optionally sent to admins)".to_string(),
-        Type::Report => "A new report has been filed".to_string(),
+/// A notification, or a group of notifications of the same type, newest first.
+pub fn notification<'a>(group: &[&'a Notification], cache: &'a Cache) -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let Some(notification) = group.first() else {
+        return widget::column().into();
     };
 
-    let action = widget::button::custom(
-        widget::row()
-            .push(
-                cache
-                    .handles
-                    .get(&notification.account.avatar)
-                    .map(|handle| widget::image(handle).width(20))
-                    .unwrap_or(utils::fallback_avatar().width(20)),
-            )
-            .push(widget::text(action))
-            .spacing(spacing.space_xs),
-    )
-    .on_press(Message::Status(
-        crate::widgets::status::Message::OpenAccount(notification.account.clone()),
-    ));
+    let avatars = group.iter().take(GROUP_AVATARS).map(|notification| {
+        widget::button::custom(
+            cache
+                .avatar(&notification.account)
+                .map(|handle| widget::image(handle).width(20))
+                .unwrap_or(utils::fallback_avatar().width(20)),
+        )
+        .class(cosmic::style::Button::Image)
+        .padding(0)
+        .on_press(Message::Status(
+            crate::widgets::status::Message::OpenAccount(notification.account.clone()),
+        ))
+        .into()
+    });
+
+    let action = widget::row()
+        .extend(avatars)
+        .push(
+            widget::button::custom(super::inline::text(
+                &summary(notification, group.len() - 1),
+                &notification.account.emojis,
+                cache,
+                14,
+            ))
+            .on_press(Message::Status(
+                crate::widgets::status::Message::OpenAccount(notification.account.clone()),
+            )),
+        )
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_xxs);
 
     let content = notification.status.as_ref().map(|status| {
         widget::container(
@@ -57,8 +85,29 @@ pub fn notification<'a>(notification: &'a Notification, cache: &'a Cache) -> Ele
         .class(cosmic::theme::Container::Dialog)
     });
 
+    let follow_request = matches!(notification.notification_type, Type::FollowRequest).then(|| {
+        let id = notification.account.id.to_string();
+        widget::row()
+            .push(
+                widget::button::suggested(fl!("accept"))
+                    .on_press(Message::AcceptFollowRequest(id.clone())),
+            )
+            .push(
+                widget::button::destructive(fl!("reject"))
+                    .on_press(Message::RejectFollowRequest(id)),
+            )
+            .spacing(spacing.space_xs)
+    });
+
+    let moderation =
+        matches!(notification.notification_type, Type::SignUp | Type::Report).then(|| {
+            widget::button::standard(fl!("open-moderation")).on_press(Message::OpenModeration)
+        });
+
     let content = widget::column()
         .push(action)
+        .push_maybe(follow_request)
+        .push_maybe(moderation)
         .push_maybe(content)
         .spacing(spacing.space_xs);
 
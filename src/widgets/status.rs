@@ -2,9 +2,10 @@ use std::str::FromStr;
 
 use cosmic::{
     app::command::Task,
-    iced::{mouse::Interaction, Alignment, Length},
+    iced::{mouse::Interaction, Alignment, Border, Length},
     iced_widget::scrollable::{Direction, Scrollbar},
-    widget, Apply, Element,
+    widget::{self, menu},
+    Apply, Element,
 };
 use mastodon_async::{
     prelude::{Account, Status, StatusId},
@@ -13,7 +14,8 @@ use mastodon_async::{
 use reqwest::Url;
 
 use crate::{
-    app,
+    app, fl,
+    html::Link,
     utils::{self, Cache},
 };
 
@@ -24,7 +26,19 @@ pub enum Message {
     Reply(StatusId, String),
     Favorite(StatusId, bool),
     Boost(StatusId, bool),
+    Bookmark(StatusId, bool),
     OpenLink(Url),
+    /// Opens the profile of a mentioned account by its id.
+    OpenMention(String),
+    OpenHashtag(String),
+    /// Opens the attachment at an index of a status in the media viewer.
+    OpenMedia(StatusId, usize),
+    /// Opens the composer with the source of an own status.
+    Edit(StatusId),
+    /// Deletes an own status, writing it again in the composer when `true`.
+    Delete(StatusId, bool),
+    Pin(StatusId, bool),
+    Mute(StatusId, bool),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -61,6 +75,7 @@ pub fn status<'a>(
 ) -> Element<'a, Message> {
     let spacing = cosmic::theme::active().cosmic().spacing;
     let reblog_button = reblog_button(cache, status);
+    let selected = cache.selected == Some(status.id.to_string());
     let status = status
         .reblog
         .as_ref()
@@ -70,14 +85,27 @@ pub fn status<'a>(
     widget::column()
         .push_maybe(reblog_button)
         .push(header(status, cache))
-        .push(content(status, options))
+        .push(content(status, options, cache))
         .push_maybe(card(status, cache))
         .push_maybe(media(status, cache, options))
         .push_maybe(tags(status, options))
-        .push_maybe(actions(status, options))
+        .push_maybe(actions(status, options, cache))
         .padding(spacing.space_xs)
         .spacing(spacing.space_xs)
         .width(Length::Fill)
+        .apply(widget::container)
+        .class(if selected {
+            cosmic::theme::Container::custom(|theme| widget::container::Style {
+                border: Border {
+                    color: theme.cosmic().accent_color().into(),
+                    width: 2.0,
+                    radius: theme.cosmic().corner_radii.radius_s.into(),
+                },
+                ..Default::default()
+            })
+        } else {
+            cosmic::theme::Container::default()
+        })
         .into()
 }
 
@@ -90,8 +118,7 @@ fn card<'a>(status: &'a Status, cache: &'a Cache) -> Option<Element<'a, Message>
                     .ok()
                     .map(|url| {
                         cache
-                            .handles
-                            .get(&url)
+                            .handle(&url)
                             .map(widget::image)
                             .unwrap_or(utils::fallback_avatar())
                     })
@@ -137,11 +164,24 @@ pub fn update(message: Message) -> Task<app::Message> {
         Message::Boost(status_id, boosted) => {
             cosmic::task::message(app::Message::Status(Message::Boost(status_id, boosted)))
         }
+        Message::Bookmark(status_id, bookmarked) => cosmic::task::message(app::Message::Status(
+            Message::Bookmark(status_id, bookmarked),
+        )),
         Message::OpenLink(url) => cosmic::task::message(app::Message::Open(url.to_string())),
+        Message::OpenMention(id) => cosmic::task::message(app::Message::OpenAccount(id)),
+        Message::OpenHashtag(hashtag) => cosmic::task::message(app::Message::OpenHashtag(hashtag)),
+        Message::OpenMedia(id, index) => cosmic::task::message(app::Message::OpenMedia(id, index)),
+        Message::Edit(_) | Message::Delete(..) | Message::Pin(..) | Message::Mute(..) => {
+            cosmic::task::message(app::Message::Status(message))
+        }
     }
 }
 
-fn actions(status: &Status, options: StatusOptions) -> Option<Element<Message>> {
+fn actions<'a>(
+    status: &'a Status,
+    options: StatusOptions,
+    cache: &'a Cache,
+) -> Option<Element<'a, Message>> {
     let spacing = cosmic::theme::active().cosmic().spacing;
 
     let actions = (options.actions).then_some({
@@ -196,12 +236,71 @@ fn actions(status: &Status, options: StatusOptions) -> Option<Element<Message>>
                             .map(|favourited| Message::Favorite(status.id.clone(), favourited)),
                     ),
             )
+            .push(
+                widget::button::icon(widget::icon::from_name("bookmark-new-symbolic"))
+                    .class(if status.bookmarked == Some(true) {
+                        cosmic::theme::Button::Suggested
+                    } else {
+                        cosmic::theme::Button::Icon
+                    })
+                    .on_press_maybe(
+                        status
+                            .bookmarked
+                            .map(|bookmarked| Message::Bookmark(status.id.clone(), bookmarked)),
+                    ),
+            )
+            .push_maybe((cache.me == Some(status.account.id.to_string())).then(|| more(status)))
+            .push_maybe(cache.pending.contains(&status.id.to_string()).then(|| {
+                widget::row()
+                    .push(widget::icon::from_name("emblem-synchronizing-symbolic").size(16))
+                    .push(widget::text::caption(fl!("pending")))
+                    .align_y(Alignment::Center)
+                    .spacing(spacing.space_xxs)
+            }))
+            .align_y(Alignment::Center)
             .spacing(spacing.space_xs)
             .into()
     });
     actions
 }
 
+/// The menu of actions on statuses of the logged-in account.
+fn more(status: &Status) -> Element<Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let id = &status.id;
+    let pinned = status.pinned.unwrap_or_default();
+    let muted = status.muted.unwrap_or_default();
+    let item = |label: String, message: Message| {
+        menu::Tree::new(menu::menu_button(vec![widget::text(label).into()]).on_press(message))
+    };
+
+    let items = vec![
+        item(fl!("edit"), Message::Edit(id.clone())),
+        item(
+            if pinned { fl!("unpin") } else { fl!("pin") },
+            Message::Pin(id.clone(), pinned),
+        ),
+        item(
+            if muted {
+                fl!("unmute-conversation")
+            } else {
+                fl!("mute-conversation")
+            },
+            Message::Mute(id.clone(), muted),
+        ),
+        item(fl!("delete"), Message::Delete(id.clone(), false)),
+        item(fl!("delete-and-redraft"), Message::Delete(id.clone(), true)),
+    ];
+
+    let root = widget::button::custom(widget::icon::from_name("view-more-symbolic").size(16))
+        .padding(spacing.space_xxs)
+        .class(cosmic::theme::Button::MenuRoot);
+
+    menu::bar(vec![menu::Tree::with_children(root, items)])
+        .item_width(menu::ItemWidth::Uniform(240))
+        .into()
+}
+
 fn media<'a>(
     status: &'a Status,
     cache: &'a Cache,
@@ -212,15 +311,15 @@ fn media<'a>(
     let attachments = status
         .media_attachments
         .iter()
-        .map(|media| {
+        .enumerate()
+        .map(|(index, media)| {
             widget::button::image(
                 cache
-                    .handles
-                    .get(&media.preview_url)
+                    .attachment(media)
                     .cloned()
                     .unwrap_or(crate::utils::fallback_handle()),
             )
-            .on_press_maybe(media.url.as_ref().cloned().map(Message::OpenLink))
+            .on_press(Message::OpenMedia(status.id.clone(), index))
             .into()
         })
         .collect::<Vec<Element<Message>>>();
@@ -244,7 +343,7 @@ fn tags(status: &Status, options: StatusOptions) -> Option<Element<Message>> {
                     .iter()
                     .map(|tag| {
                         widget::button::suggested(format!("#{}", tag.name.clone()))
-                            .on_press_maybe(Url::from_str(&tag.url).map(Message::OpenLink).ok())
+                            .on_press(Message::OpenHashtag(tag.name.clone()))
                             .into()
                     })
                     .collect::<Vec<Element<Message>>>(),
@@ -264,8 +363,7 @@ fn header<'a>(
         .push(
             widget::button::image(
                 cache
-                    .handles
-                    .get(&status.account.avatar)
+                    .avatar(&status.account)
                     .cloned()
                     .unwrap_or(crate::utils::fallback_handle()),
             )
@@ -275,7 +373,12 @@ fn header<'a>(
         )
         .push(
             widget::column()
-                .push(widget::text(status.account.display_name.clone()).size(18))
+                .push(super::inline::text(
+                    &status.account.display_name,
+                    &status.account.emojis,
+                    cache,
+                    18,
+                ))
                 .push(
                     widget::button::link(format!("@{}", status.account.username.clone()))
                         .on_press(Message::OpenAccount(status.account.clone())),
@@ -286,13 +389,30 @@ fn header<'a>(
     header
 }
 
-fn content(status: &Status, options: StatusOptions) -> Element<Message> {
-    let mut status_text: Element<_> = widget::text(
-        html2text::config::rich()
-            .string_from_read(status.content.as_bytes(), 700)
-            .unwrap(),
-    )
-    .into();
+fn content<'a>(
+    status: &'a Status,
+    options: StatusOptions,
+    cache: &'a Cache,
+) -> Element<'a, Message> {
+    let mut status_text =
+        super::inline::html(
+            &status.content,
+            &status.emojis,
+            cache,
+            14,
+            |link| match link {
+                Link::Url(url) => Message::OpenLink(url.clone()),
+                // Mentions of remote accounts link to their home server, which knows
+                // them by another id than ours.
+                Link::Mention(url) => status
+                    .mentions
+                    .iter()
+                    .find(|mention| mention.url.as_str() == url.as_str())
+                    .map(|mention| Message::OpenMention(mention.id.to_string()))
+                    .unwrap_or_else(|| Message::OpenLink(url.clone())),
+                Link::Hashtag(hashtag) => Message::OpenHashtag(hashtag.clone()),
+            },
+        );
 
     if options.expand {
         status_text = widget::MouseArea::new(status_text)
@@ -311,15 +431,16 @@ fn reblog_button<'a>(cache: &'a Cache, status: &'a Status) -> Option<widget::But
             widget::row()
                 .push(
                     cache
-                        .handles
-                        .get(&status.account.avatar)
+                        .avatar(&status.account)
                         .map(|avatar| widget::image(avatar).width(20).height(20))
                         .unwrap_or(crate::utils::fallback_avatar().width(20).height(20)),
                 )
-                .push(widget::text(format!(
-                    "{} boosted",
-                    status.account.display_name
-                )))
+                .push(super::inline::text(
+                    &format!("{} boosted", status.account.display_name),
+                    &status.account.emojis,
+                    cache,
+                    14,
+                ))
                 .spacing(spacing.space_xs),
         )
         .on_press(Message::OpenAccount(status.account.clone())),